@@ -0,0 +1,7 @@
+-- Add migration script here
+START TRANSACTION;
+ALTER TABLE hypernet_raffles
+    ADD COLUMN owned_tickets int null;
+ALTER TABLE hypernet_raffles
+    ADD COLUMN owned_tickets_manual boolean default false not null;
+COMMIT TRANSACTION;
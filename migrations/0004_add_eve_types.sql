@@ -0,0 +1,8 @@
+-- Add migration script here
+CREATE TABLE eve_types
+(
+    type_id int primary key not null,
+    name    text            not null
+);
+
+CREATE INDEX eve_types_name_idx ON eve_types (lower(name));
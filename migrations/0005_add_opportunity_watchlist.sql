@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TABLE opportunity_watchlist
+(
+    id              serial primary key not null,
+    type_id         int                null,
+    market_group_id int                null,
+    CHECK ((type_id IS NULL) != (market_group_id IS NULL))
+);
+
+CREATE UNIQUE INDEX opportunity_watchlist_type_idx ON opportunity_watchlist (type_id);
+CREATE UNIQUE INDEX opportunity_watchlist_market_group_idx ON opportunity_watchlist (market_group_id);
@@ -0,0 +1,8 @@
+-- Add migration script here
+CREATE TABLE fill_models
+(
+    id           serial primary key                                 not null,
+    trained_at   TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null,
+    sample_count int                                                not null,
+    model        text                                               not null
+);
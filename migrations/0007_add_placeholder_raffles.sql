@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE hypernet_raffles
+    ADD COLUMN placeholder boolean default false not null;
@@ -0,0 +1,16 @@
+-- Add migration script here
+CREATE type processed_notification_state as ENUM ('Pending', 'Delivered');
+
+CREATE TABLE processed_notifications
+(
+    notification_id   int8 primary key                                   not null,
+    character_id      int references eve_character_info (character_id)   not null,
+    raffle_id         text references hypernet_raffles (raffle_id)       not null,
+    notification_type text                                               not null,
+    status            hypernet_raffle_status                             not null,
+    state             processed_notification_state                       not null,
+    created_at        TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null,
+    delivered_at      TIMESTAMP WITH TIME ZONE                           null
+);
+
+CREATE INDEX processed_notifications_pending_idx ON processed_notifications (character_id) WHERE state = 'Pending';
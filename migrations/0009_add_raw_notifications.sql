@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE raw_notifications
+(
+    notification_id   int8 primary key                                   not null,
+    character_id      int references eve_character_info (character_id)   not null,
+    notification_type text                                               not null,
+    timestamp         TIMESTAMP WITH TIME ZONE                           not null,
+    sender_id         int                                                not null,
+    sender_type       text                                               not null,
+    text              text                                               null,
+    archived_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null
+);
@@ -0,0 +1,31 @@
+-- Add migration script here
+CREATE type raffle_event_kind as ENUM ('Created', 'StatusChanged', 'ResultSet', 'PriceSnapshot', 'Correction');
+
+CREATE TABLE raffle_events
+(
+    id              bigserial primary key                              not null,
+    raffle_id       text references hypernet_raffles (raffle_id)       not null,
+    kind            raffle_event_kind                                  not null,
+    status          hypernet_raffle_status                             null,
+    result          hypernet_raffle_result                             null,
+    notification_id int8                                               null,
+    discord_user_id int8                                               null,
+    details         text                                               null,
+    occurred_at     TIMESTAMP WITH TIME ZONE                           not null,
+    recorded_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null
+);
+
+CREATE INDEX raffle_events_raffle_id_idx ON raffle_events (raffle_id, occurred_at);
+
+CREATE FUNCTION raffle_events_append_only() RETURNS trigger AS
+$$
+BEGIN
+    RAISE EXCEPTION 'raffle_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER raffle_events_append_only
+    BEFORE UPDATE OR DELETE
+    ON raffle_events
+    FOR EACH ROW
+EXECUTE FUNCTION raffle_events_append_only();
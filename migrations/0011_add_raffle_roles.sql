@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE type hypernet_raffle_role as ENUM ('Owner', 'Participant');
+
+ALTER TABLE hypernet_raffles
+    ADD COLUMN role hypernet_raffle_role not null default 'Owner';
+ALTER TABLE hypernet_raffles
+    ADD COLUMN isk_spent float8 null;
+
+UPDATE hypernet_raffles
+SET role = 'Participant'
+WHERE owner_id != character_id;
@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE hypernet_raffles
+    ADD COLUMN asset_baseline int null;
+ALTER TABLE hypernet_raffles
+    ADD COLUMN result_manual bool not null default false;
+
+UPDATE hypernet_raffles
+SET result_manual = true
+WHERE result != 'None';
@@ -0,0 +1,15 @@
+-- Add migration script here
+CREATE type raffle_wallet_entry_kind as ENUM ('TicketPurchase', 'Payout', 'Tax', 'Refund');
+
+CREATE TABLE raffle_wallet_entries
+(
+    journal_id   int8 primary key                                 not null,
+    raffle_id    text references hypernet_raffles (raffle_id)     not null,
+    character_id int references eve_character_info (character_id) not null,
+    kind         raffle_wallet_entry_kind                         not null,
+    amount       float8                                           not null,
+    ref_type     text                                             not null,
+    date         TIMESTAMP WITH TIME ZONE                         not null
+);
+
+CREATE INDEX raffle_wallet_entries_raffle_id_idx ON raffle_wallet_entries (raffle_id);
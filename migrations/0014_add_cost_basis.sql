@@ -0,0 +1,18 @@
+-- Add migration script here
+CREATE TABLE wallet_transactions
+(
+    transaction_id int8 primary key                                 not null,
+    character_id   int references eve_character_info (character_id) not null,
+    type_id        int                                              not null,
+    quantity       int                                              not null,
+    unit_price     float8                                           not null,
+    is_buy         bool                                             not null,
+    date           TIMESTAMP WITH TIME ZONE                         not null
+);
+
+CREATE INDEX wallet_transactions_character_type_idx ON wallet_transactions (character_id, type_id, date);
+
+ALTER TABLE hypernet_raffles
+    ADD COLUMN item_cost_basis float8 null;
+ALTER TABLE hypernet_raffles
+    ADD COLUMN hypercore_unit_cost float8 null;
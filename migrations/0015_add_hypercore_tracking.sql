@@ -0,0 +1,20 @@
+-- Add migration script here
+CREATE TABLE hypercore_consumptions
+(
+    raffle_id    text primary key references hypernet_raffles (raffle_id) not null,
+    character_id int references eve_character_info (character_id)         not null,
+    quantity     int                                                      not null,
+    consumed_at  TIMESTAMP WITH TIME ZONE                                 not null
+);
+
+CREATE INDEX hypercore_consumptions_character_idx ON hypercore_consumptions (character_id, consumed_at);
+
+CREATE TABLE hypercore_inventory
+(
+    character_id   int primary key references eve_character_info (character_id) not null,
+    quantity       int                                                         not null,
+    average_cost   float8                                                      null,
+    daily_usage    float8                                                      not null,
+    low_alert_sent bool                     DEFAULT false                      not null,
+    updated_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP          not null
+);
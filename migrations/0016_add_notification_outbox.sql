@@ -0,0 +1,23 @@
+-- Add migration script here
+CREATE type outbox_state as ENUM ('Pending', 'Sent', 'Failed');
+
+CREATE TABLE notification_outbox
+(
+    id              bigserial primary key                              not null,
+    character_id    int references eve_character_info (character_id)   not null,
+    raffle_id       text references hypernet_raffles (raffle_id)       null,
+    payload         text                                               not null,
+    state           outbox_state             DEFAULT 'Pending'         not null,
+    attempts        int                      DEFAULT 0                 not null,
+    next_attempt_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null,
+    last_error      text                                               null,
+    created_at      TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null,
+    sent_at         TIMESTAMP WITH TIME ZONE                           null
+);
+
+CREATE INDEX notification_outbox_due_idx ON notification_outbox (next_attempt_at) WHERE state = 'Pending';
+
+ALTER TABLE notification_channel_map
+    ADD COLUMN disabled_at TIMESTAMP WITH TIME ZONE null;
+ALTER TABLE notification_channel_map
+    ADD COLUMN disabled_reason text null;
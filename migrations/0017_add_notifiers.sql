@@ -0,0 +1,51 @@
+-- Add migration script here
+CREATE type notification_event_kind as ENUM ('Created', 'Expired', 'Finished', 'LowHypercores');
+CREATE type notification_backend as ENUM ('DiscordChannel', 'DirectMessage', 'Webhook', 'EveMail');
+
+-- Without rows for an event kind the user gets it through the Discord channel (or DM)
+CREATE TABLE notification_backend_preferences
+(
+    discord_user_id bigint                  not null,
+    event_kind      notification_event_kind not null,
+    backend         notification_backend    not null,
+    primary key (discord_user_id, event_kind, backend)
+);
+
+CREATE TABLE webhook_endpoints
+(
+    discord_user_id bigint primary key                                 not null,
+    url             text                                               not null,
+    secret          text                                               not null,
+    created_at      TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null
+);
+
+ALTER TABLE notification_outbox
+    ADD COLUMN event_kind notification_event_kind DEFAULT 'Created' not null;
+ALTER TABLE notification_outbox
+    ADD COLUMN backend notification_backend DEFAULT 'DiscordChannel' not null;
+
+-- Messages queued before this migration carried a plain Discord message. They stay queued for
+-- the Discord channel they were meant for, wrapped into an event the sender can read.
+UPDATE notification_outbox
+SET event_kind = CASE
+                     WHEN raffle_id IS NULL THEN 'LowHypercores'
+                     WHEN payload::jsonb #>> '{embeds,0,title}' LIKE 'Hypernet Raffle Expired%'
+                         THEN 'Expired'
+                     WHEN payload::jsonb #>> '{embeds,0,title}' LIKE 'Hypernet Raffle Finished%'
+                         THEN 'Finished'
+                     ELSE 'Created' END::notification_event_kind,
+    backend    = 'DiscordChannel'
+WHERE state = 'Pending';
+
+UPDATE notification_outbox o
+SET payload = jsonb_build_object(
+        'kind', o.event_kind,
+        'character_id', o.character_id,
+        'character_name', c.character_name,
+        'raffle', null,
+        'occurred_at', o.created_at,
+        'discord', o.payload::jsonb
+              )::text
+FROM eve_character_info c
+WHERE c.character_id = o.character_id
+  AND o.state = 'Pending';
@@ -0,0 +1,40 @@
+-- Add migration script here
+ALTER TYPE notification_event_kind ADD VALUE 'ResultSet';
+
+-- Webhooks belong to a user or to a guild. A guild webhook gets the raffle events of every user
+-- whose notification channel is in that guild.
+ALTER TABLE webhook_endpoints
+    DROP CONSTRAINT webhook_endpoints_pkey;
+ALTER TABLE webhook_endpoints
+    ADD COLUMN id bigserial primary key;
+ALTER TABLE webhook_endpoints
+    ALTER COLUMN discord_user_id DROP NOT NULL;
+ALTER TABLE webhook_endpoints
+    ADD COLUMN guild_id bigint null;
+ALTER TABLE webhook_endpoints
+    ADD CONSTRAINT webhook_endpoints_discord_user_id_key UNIQUE (discord_user_id);
+ALTER TABLE webhook_endpoints
+    ADD CONSTRAINT webhook_endpoints_guild_id_key UNIQUE (guild_id);
+ALTER TABLE webhook_endpoints
+    ADD CONSTRAINT webhook_endpoints_owner_check CHECK ((discord_user_id IS NULL) <> (guild_id IS NULL));
+
+ALTER TABLE notification_channel_map
+    ADD COLUMN guild_id bigint null;
+
+ALTER TABLE notification_outbox
+    ADD COLUMN webhook_id bigint references webhook_endpoints (id) on delete set null null;
+
+CREATE TABLE webhook_deliveries
+(
+    id           bigserial primary key                                         not null,
+    webhook_id   bigint references webhook_endpoints (id) on delete cascade    not null,
+    -- Null for test deliveries
+    outbox_id    bigint references notification_outbox (id)                    null,
+    event        text                                                          not null,
+    status_code  int                                                           null,
+    error        text                                                          null,
+    duration_ms  int                                                           not null,
+    attempted_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP            not null
+);
+
+CREATE INDEX webhook_deliveries_webhook_idx ON webhook_deliveries (webhook_id, attempted_at);
@@ -0,0 +1,23 @@
+-- Add migration script here
+ALTER TYPE notification_event_kind ADD VALUE 'ResultReminder';
+
+-- Without a row an event is enabled, goes to the user's notification channel and has no
+-- thresholds. Created raffles are the exception, they stay quiet unless enabled here.
+CREATE TABLE notification_preferences
+(
+    character_id       int references eve_character_info (character_id)   not null,
+    event_kind         notification_event_kind                            not null,
+    enabled            bool                                               not null,
+    -- Overrides the notification channel of the user
+    channel_id         bigint                                             null,
+    -- Skip raffles whose item is worth less than this (sell price)
+    min_value          float8                                             null,
+    -- Skip raffles whose expected value is below this
+    min_expected_value float8                                             null,
+    updated_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null,
+    primary key (character_id, event_kind)
+);
+
+-- Queued messages keep going to the user's notification channel
+ALTER TABLE notification_outbox
+    ADD COLUMN channel_id bigint null;
@@ -0,0 +1,6 @@
+-- Add migration script here
+-- The Discord message that follows the raffle, edited on every transition. Raffles go
+-- through their events in one message now, so created raffles are announced by default.
+ALTER TABLE hypernet_raffles
+    ADD COLUMN discord_channel_id bigint null,
+    ADD COLUMN discord_message_id bigint null;
@@ -0,0 +1,23 @@
+-- Add migration script here
+-- Users in digest mode get their raffle messages bundled into one. Without a window the
+-- digest goes out after every collection run.
+CREATE TABLE notification_digest_settings
+(
+    discord_user_id bigint primary key                                 not null,
+    window_minutes  int                                                null,
+    updated_at      TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null
+);
+
+CREATE TABLE notification_digests
+(
+    id              bigserial primary key                              not null,
+    discord_user_id bigint                                             not null,
+    created_at      TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP not null
+);
+
+-- Held messages wait for their digest, the ones of a sent digest are its entries
+ALTER TABLE notification_outbox
+    ADD COLUMN digest bool DEFAULT false not null,
+    ADD COLUMN digest_id bigint references notification_digests (id) null;
+
+CREATE INDEX notification_outbox_digest_idx ON notification_outbox (digest_id);
@@ -0,0 +1,5 @@
+-- Add migration script here
+-- Finished raffles without a result get reminders, fewer and fewer of them
+ALTER TABLE hypernet_raffles
+    ADD COLUMN result_reminders        int DEFAULT 0 not null,
+    ADD COLUMN last_result_reminder_at TIMESTAMP WITH TIME ZONE null;
@@ -0,0 +1,4 @@
+-- Add migration script here
+-- Claimed by the sender. Its next_attempt_at is when the claim runs out, a message whose send
+-- was cut off is picked up again then.
+ALTER TYPE outbox_state ADD VALUE 'Sending';
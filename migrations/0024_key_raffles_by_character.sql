@@ -0,0 +1,98 @@
+-- Add migration script here
+-- The owner and a participant of the same raffle can both be tracked, each with their own row,
+-- so raffles are keyed by the raffle and the character that sees it. Raffles tracked so far were
+-- stored once per raffle id and keep the character they were stored for.
+ALTER TABLE processed_notifications
+    DROP CONSTRAINT processed_notifications_raffle_id_fkey;
+ALTER TABLE raffle_events
+    DROP CONSTRAINT raffle_events_raffle_id_fkey;
+ALTER TABLE raffle_wallet_entries
+    DROP CONSTRAINT raffle_wallet_entries_raffle_id_fkey;
+ALTER TABLE hypercore_consumptions
+    DROP CONSTRAINT hypercore_consumptions_raffle_id_fkey;
+ALTER TABLE notification_outbox
+    DROP CONSTRAINT notification_outbox_raffle_id_fkey;
+
+ALTER TABLE hypernet_raffles
+    DROP CONSTRAINT hypernet_raffles_pkey;
+ALTER TABLE hypernet_raffles
+    ADD PRIMARY KEY (raffle_id, character_id);
+
+-- Rows of other characters that were attached to the shared raffle get a raffle of their own.
+-- Their per-character state starts over, the wallet reconciliation and result detection fill it in.
+INSERT INTO hypernet_raffles(location_id, owner_id, character_id, raffle_id, ticket_count, ticket_price, type_id,
+                             status, result, created_at, sell_price, buy_price, hypercore_buy_price,
+                             hypercore_sell_price, plex_price, placeholder, role)
+SELECT r.location_id,
+       r.owner_id,
+       m.character_id,
+       r.raffle_id,
+       r.ticket_count,
+       r.ticket_price,
+       r.type_id,
+       r.status,
+       'None',
+       r.created_at,
+       r.sell_price,
+       r.buy_price,
+       r.hypercore_buy_price,
+       r.hypercore_sell_price,
+       r.plex_price,
+       r.placeholder,
+       CASE WHEN r.owner_id = m.character_id THEN 'Owner' ELSE 'Participant' END::hypernet_raffle_role
+FROM (SELECT raffle_id, character_id
+      FROM processed_notifications
+      UNION
+      SELECT raffle_id, character_id
+      FROM raffle_wallet_entries
+      UNION
+      SELECT raffle_id, character_id
+      FROM hypercore_consumptions
+      UNION
+      SELECT raffle_id, character_id
+      FROM notification_outbox
+      WHERE raffle_id IS NOT NULL) m
+         JOIN hypernet_raffles r ON r.raffle_id = m.raffle_id
+ON CONFLICT DO NOTHING;
+
+-- Events caused by a notification belong to the character that received it
+ALTER TABLE raffle_events
+    ADD COLUMN character_id int null;
+ALTER TABLE raffle_events
+    DISABLE TRIGGER raffle_events_append_only;
+UPDATE raffle_events e
+SET character_id = coalesce((SELECT p.character_id
+                             FROM processed_notifications p
+                             WHERE p.notification_id = e.notification_id),
+                            (SELECT min(r.character_id)
+                             FROM hypernet_raffles r
+                             WHERE r.raffle_id = e.raffle_id
+                               AND r.role = 'Owner'),
+                            (SELECT min(r.character_id)
+                             FROM hypernet_raffles r
+                             WHERE r.raffle_id = e.raffle_id));
+ALTER TABLE raffle_events
+    ENABLE TRIGGER raffle_events_append_only;
+ALTER TABLE raffle_events
+    ALTER COLUMN character_id SET NOT NULL;
+DROP INDEX raffle_events_raffle_id_idx;
+CREATE INDEX raffle_events_raffle_id_idx ON raffle_events (raffle_id, character_id, occurred_at);
+
+ALTER TABLE hypercore_consumptions
+    DROP CONSTRAINT hypercore_consumptions_pkey;
+ALTER TABLE hypercore_consumptions
+    ADD PRIMARY KEY (raffle_id, character_id);
+
+DROP INDEX raffle_wallet_entries_raffle_id_idx;
+CREATE INDEX raffle_wallet_entries_raffle_id_idx ON raffle_wallet_entries (raffle_id, character_id);
+
+ALTER TABLE processed_notifications
+    ADD FOREIGN KEY (raffle_id, character_id) REFERENCES hypernet_raffles (raffle_id, character_id);
+ALTER TABLE raffle_events
+    ADD FOREIGN KEY (raffle_id, character_id) REFERENCES hypernet_raffles (raffle_id, character_id);
+ALTER TABLE raffle_wallet_entries
+    ADD FOREIGN KEY (raffle_id, character_id) REFERENCES hypernet_raffles (raffle_id, character_id);
+ALTER TABLE hypercore_consumptions
+    ADD FOREIGN KEY (raffle_id, character_id) REFERENCES hypernet_raffles (raffle_id, character_id);
+ALTER TABLE notification_outbox
+    ADD FOREIGN KEY (raffle_id, character_id) REFERENCES hypernet_raffles (raffle_id, character_id);
@@ -0,0 +1,4 @@
+SELECT *
+FROM eve_character_info
+WHERE discord_user_id = $1
+ORDER BY character_name;
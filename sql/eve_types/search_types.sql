@@ -0,0 +1,5 @@
+SELECT type_id, name
+FROM eve_types
+WHERE name ILIKE '%' || $1 || '%' ESCAPE '\'
+ORDER BY length(name), name
+LIMIT 25;
@@ -0,0 +1,3 @@
+SELECT type_id, name
+FROM eve_types
+WHERE lower(name) = lower($1);
@@ -0,0 +1,2 @@
+SELECT type_id
+FROM eve_types;
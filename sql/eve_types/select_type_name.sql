@@ -0,0 +1,3 @@
+SELECT name
+FROM eve_types
+WHERE type_id = $1;
@@ -0,0 +1,3 @@
+INSERT INTO eve_types (type_id, name)
+VALUES ($1, $2)
+ON CONFLICT (type_id) DO UPDATE SET name = excluded.name;
@@ -0,0 +1,4 @@
+INSERT INTO eve_types (type_id, name)
+SELECT *
+FROM unnest($1::int[], $2::text[])
+ON CONFLICT (type_id) DO UPDATE SET name = excluded.name;
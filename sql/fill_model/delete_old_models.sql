@@ -0,0 +1,3 @@
+DELETE
+FROM fill_models
+WHERE id != $1;
@@ -0,0 +1,3 @@
+INSERT INTO fill_models (sample_count, model)
+VALUES ($1, $2)
+RETURNING id;
@@ -0,0 +1,4 @@
+SELECT model
+FROM fill_models
+ORDER BY trained_at DESC
+LIMIT 1;
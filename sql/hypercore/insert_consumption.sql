@@ -0,0 +1,3 @@
+INSERT INTO hypercore_consumptions(raffle_id, character_id, quantity, consumed_at)
+VALUES ($1, $2, $3, $4)
+on conflict do nothing;
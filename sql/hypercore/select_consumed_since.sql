@@ -0,0 +1,4 @@
+SELECT coalesce(sum(quantity), 0) as "consumed!"
+FROM hypercore_consumptions
+WHERE character_id = $1
+  AND consumed_at > $2;
@@ -0,0 +1,8 @@
+SELECT character_id,
+       quantity,
+       average_cost,
+       daily_usage,
+       low_alert_sent,
+       updated_at
+FROM hypercore_inventory
+WHERE character_id = $1;
@@ -0,0 +1,7 @@
+INSERT INTO hypercore_inventory(character_id, quantity, average_cost, daily_usage, low_alert_sent, updated_at)
+VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP)
+ON CONFLICT (character_id) DO UPDATE SET quantity       = excluded.quantity,
+                                         average_cost   = excluded.average_cost,
+                                         daily_usage    = excluded.daily_usage,
+                                         low_alert_sent = excluded.low_alert_sent,
+                                         updated_at     = excluded.updated_at;
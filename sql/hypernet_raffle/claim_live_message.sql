@@ -0,0 +1,7 @@
+-- Adopts the message a result was picked on if the raffle has none yet
+UPDATE hypernet_raffles
+SET discord_channel_id = $3,
+    discord_message_id = $4
+WHERE raffle_id = $1
+  AND character_id = $2
+  AND discord_message_id IS NULL;
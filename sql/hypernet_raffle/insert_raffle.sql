@@ -1,6 +1,6 @@
 INSERT INTO hypernet_raffles(location_id, owner_id, character_id, raffle_id, ticket_count, ticket_price, type_id,
                              status, result, created_at, sell_price, buy_price, hypercore_buy_price,
-                             hypercore_sell_price, plex_price)
+                             hypercore_sell_price, plex_price, owned_tickets, placeholder, role, isk_spent)
 VALUES ($1,
         $2,
         $3,
@@ -14,5 +14,5 @@ VALUES ($1,
         $11,
         $12,
         $13,
-        $14, $15)
-on conflict do nothing;
\ No newline at end of file
+        $14, $15, $16, $17, $18, $19)
+on conflict (raffle_id, character_id) do nothing;
\ No newline at end of file
@@ -0,0 +1,5 @@
+UPDATE hypernet_raffles
+SET result_reminders        = result_reminders + 1,
+    last_result_reminder_at = $3
+WHERE raffle_id = $1
+  AND character_id = $2;
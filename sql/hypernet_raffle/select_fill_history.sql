@@ -0,0 +1,13 @@
+SELECT type_id,
+       ticket_count,
+       ticket_price,
+       sell_price,
+       status as "status: HypernetRaffleStatus"
+FROM hypernet_raffles
+WHERE role = 'Owner'
+  AND NOT placeholder
+  AND status IN ('Finished', 'Expired')
+  AND ticket_count > 0
+  AND ticket_price > 0
+  AND sell_price IS NOT NULL
+  AND sell_price > 0;
@@ -0,0 +1,4 @@
+SELECT discord_channel_id, discord_message_id
+FROM hypernet_raffles
+WHERE raffle_id = $1
+  AND character_id = $2;
@@ -0,0 +1,6 @@
+SELECT raffle_id,
+       owned_tickets IS NULL                         as "owned_tickets_missing!",
+       asset_baseline IS NULL AND status = 'Created' as "asset_baseline_missing!"
+FROM hypernet_raffles
+WHERE character_id = $1
+  AND raffle_id = ANY ($2);
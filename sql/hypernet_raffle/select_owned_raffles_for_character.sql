@@ -0,0 +1,25 @@
+SELECT location_id,
+       owner_id,
+       character_id,
+       raffle_id,
+       ticket_count,
+       ticket_price,
+       type_id,
+       status as "status: HypernetRaffleStatus",
+       result as "result: HypernetRaffleResult",
+       created_at,
+       buy_price,
+       sell_price,
+       hypercore_buy_price,
+       hypercore_sell_price,
+       plex_price,
+       owned_tickets,
+       placeholder,
+       role as "role: HypernetRaffleRole",
+       isk_spent,
+       item_cost_basis,
+       hypercore_unit_cost
+FROM hypernet_raffles
+WHERE character_id = $1
+  AND role = 'Owner'
+ORDER BY created_at;
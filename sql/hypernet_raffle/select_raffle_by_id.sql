@@ -12,6 +12,13 @@ SELECT location_id,
        sell_price,
        hypercore_buy_price,
        hypercore_sell_price,
-       plex_price
+       plex_price,
+       owned_tickets,
+       placeholder,
+       role as "role: HypernetRaffleRole",
+       isk_spent,
+       item_cost_basis,
+       hypercore_unit_cost
 FROM hypernet_raffles
-WHERE raffle_id = $1;
\ No newline at end of file
+WHERE raffle_id = $1
+  AND character_id = $2;
\ No newline at end of file
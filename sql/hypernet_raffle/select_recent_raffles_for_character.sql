@@ -0,0 +1,15 @@
+SELECT r.raffle_id,
+       r.role as "role: HypernetRaffleRole",
+       r.status as "status: HypernetRaffleStatus",
+       r.ticket_count,
+       r.ticket_price,
+       r.created_at,
+       (SELECT max(e.occurred_at)
+        FROM raffle_events e
+        WHERE e.raffle_id = r.raffle_id
+          AND e.character_id = r.character_id
+          AND e.kind = 'StatusChanged') as ended_at
+FROM hypernet_raffles r
+WHERE r.character_id = $1
+  AND r.created_at > $2
+ORDER BY r.created_at;
@@ -0,0 +1,15 @@
+SELECT r.raffle_id,
+       r.character_id,
+       r.result_reminders,
+       r.last_result_reminder_at,
+       coalesce((SELECT max(e.occurred_at)
+                 FROM raffle_events e
+                 WHERE e.raffle_id = r.raffle_id
+                   AND e.character_id = r.character_id
+                   AND e.kind = 'StatusChanged'
+                   AND e.status = 'Finished'), r.created_at) as "finished_at!"
+FROM hypernet_raffles r
+WHERE r.status = 'Finished'
+  AND r.result = 'None'
+  AND r.result_reminders < $1
+ORDER BY r.character_id;
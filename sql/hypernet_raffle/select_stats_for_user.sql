@@ -0,0 +1,13 @@
+SELECT r.role as "role!: HypernetRaffleRole",
+       count(*)                                      as "raffles!",
+       count(*) FILTER (WHERE r.result = 'Winner')   as "won!",
+       count(*) FILTER (WHERE r.result = 'Loser')    as "lost!",
+       count(*) FILTER (WHERE r.status = 'Expired')  as "expired!",
+       coalesce(sum(r.owned_tickets), 0)             as "tickets!",
+       coalesce(sum(r.isk_spent), 0)                 as "isk_spent!",
+       coalesce(sum(r.buy_price) FILTER (WHERE r.result = 'Winner' AND r.role = 'Participant'), 0) as "value_won!"
+FROM hypernet_raffles r
+         JOIN eve_character_info c ON c.character_id = r.character_id
+WHERE c.discord_user_id = $1
+GROUP BY r.role
+ORDER BY r.role;
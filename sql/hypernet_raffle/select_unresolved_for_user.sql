@@ -0,0 +1,27 @@
+SELECT r.location_id,
+       r.owner_id,
+       r.character_id,
+       r.raffle_id,
+       r.ticket_count,
+       r.ticket_price,
+       r.type_id,
+       r.status as "status: HypernetRaffleStatus",
+       r.result as "result: HypernetRaffleResult",
+       r.created_at,
+       r.buy_price,
+       r.sell_price,
+       r.hypercore_buy_price,
+       r.hypercore_sell_price,
+       r.plex_price,
+       r.owned_tickets,
+       r.placeholder,
+       r.role as "role: HypernetRaffleRole",
+       r.isk_spent,
+       r.item_cost_basis,
+       r.hypercore_unit_cost
+FROM hypernet_raffles r
+         JOIN eve_character_info c ON c.character_id = r.character_id
+WHERE c.discord_user_id = $1
+  AND r.status = 'Finished'
+  AND r.result = 'None'
+ORDER BY r.created_at;
@@ -0,0 +1,19 @@
+-- Only raffles with an asset baseline can be resolved from assets, participants never have one
+SELECT r.raffle_id,
+       r.character_id,
+       r.type_id,
+       r.location_id,
+       r.asset_baseline as "asset_baseline!",
+       r.created_at,
+       coalesce((SELECT max(e.occurred_at)
+                 FROM raffle_events e
+                 WHERE e.raffle_id = r.raffle_id
+                   AND e.character_id = r.character_id
+                   AND e.kind = 'StatusChanged'
+                   AND e.status = 'Finished'), r.created_at) as "finished_at!"
+FROM hypernet_raffles r
+WHERE r.status = 'Finished'
+  AND r.result = 'None'
+  AND NOT r.result_manual
+  AND r.asset_baseline IS NOT NULL
+ORDER BY r.character_id;
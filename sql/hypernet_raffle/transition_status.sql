@@ -0,0 +1,5 @@
+UPDATE hypernet_raffles
+SET status = $4
+WHERE raffle_id = $1
+  AND character_id = $2
+  AND status = $3;
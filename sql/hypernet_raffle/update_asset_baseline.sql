@@ -0,0 +1,6 @@
+UPDATE hypernet_raffles
+SET asset_baseline = $3
+WHERE raffle_id = $1
+  AND character_id = $2
+  AND asset_baseline IS NULL
+  AND status = 'Created';
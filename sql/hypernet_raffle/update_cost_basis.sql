@@ -0,0 +1,5 @@
+UPDATE hypernet_raffles
+SET item_cost_basis     = $3,
+    hypercore_unit_cost = $4
+WHERE raffle_id = $1
+  AND character_id = $2;
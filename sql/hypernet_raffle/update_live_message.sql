@@ -0,0 +1,5 @@
+UPDATE hypernet_raffles
+SET discord_channel_id = $3,
+    discord_message_id = $4
+WHERE raffle_id = $1
+  AND character_id = $2;
@@ -0,0 +1,5 @@
+UPDATE hypernet_raffles
+SET owned_tickets        = $3,
+    owned_tickets_manual = true
+WHERE raffle_id = $1
+  AND character_id = $2;
@@ -0,0 +1,6 @@
+UPDATE hypernet_raffles
+SET owned_tickets = $3,
+    isk_spent     = $4
+WHERE raffle_id = $1
+  AND character_id = $2
+  AND NOT owned_tickets_manual;
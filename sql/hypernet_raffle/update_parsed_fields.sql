@@ -0,0 +1,10 @@
+UPDATE hypernet_raffles
+SET location_id  = $3,
+    owner_id     = $4,
+    ticket_count = $5,
+    ticket_price = $6,
+    type_id      = $7,
+    created_at   = $8,
+    placeholder  = $9
+WHERE raffle_id = $1
+  AND character_id = $2;
@@ -1 +1 @@
-UPDATE hypernet_raffles SET result = $2 WHERE raffle_id = $1;
\ No newline at end of file
+UPDATE hypernet_raffles SET result = $3, result_manual = true WHERE raffle_id = $1 AND character_id = $2;
@@ -0,0 +1,6 @@
+UPDATE hypernet_raffles
+SET result = $3
+WHERE raffle_id = $1
+  AND character_id = $2
+  AND result = 'None'
+  AND NOT result_manual;
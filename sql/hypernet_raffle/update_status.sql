@@ -1 +0,0 @@
-UPDATE hypernet_raffles SET status = $2 WHERE raffle_id = $1;
\ No newline at end of file
@@ -0,0 +1,5 @@
+DELETE
+FROM notification_backend_preferences
+WHERE discord_user_id = $1
+  AND event_kind = $2
+  AND backend = $3;
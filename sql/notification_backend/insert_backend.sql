@@ -0,0 +1,3 @@
+INSERT INTO notification_backend_preferences (discord_user_id, event_kind, backend)
+VALUES ($1, $2, $3)
+on conflict do nothing;
@@ -0,0 +1,5 @@
+SELECT event_kind as "event_kind: NotificationEventKind",
+       backend as "backend: NotificationBackend"
+FROM notification_backend_preferences
+WHERE discord_user_id = $1
+ORDER BY event_kind, backend;
@@ -0,0 +1,6 @@
+UPDATE notification_channel_map
+SET disabled_at     = CURRENT_TIMESTAMP,
+    disabled_reason = $2
+WHERE channel_id = $1
+  AND disabled_at IS NULL
+RETURNING discord_user_id;
@@ -1,3 +1,6 @@
-INSERT INTO notification_channel_map (discord_user_id, channel_id)
-VALUES ($1, $2)
-on conflict (discord_user_id) do update set channel_id = excluded.channel_id;
\ No newline at end of file
+INSERT INTO notification_channel_map (discord_user_id, channel_id, guild_id)
+VALUES ($1, $2, $3)
+on conflict (discord_user_id) do update set channel_id      = excluded.channel_id,
+                                            guild_id        = excluded.guild_id,
+                                            disabled_at     = null,
+                                            disabled_reason = null;
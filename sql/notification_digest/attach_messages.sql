@@ -0,0 +1,7 @@
+-- Everything held for the user goes into the digest, due or not
+UPDATE notification_outbox
+SET digest_id = $2
+WHERE digest
+  AND digest_id IS NULL
+  AND state = 'Pending'
+  AND character_id IN (SELECT character_id FROM eve_character_info WHERE discord_user_id = $1);
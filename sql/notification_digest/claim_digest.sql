@@ -0,0 +1,6 @@
+-- Claims the messages of a digest while it is sent, like claim_due_messages.sql
+UPDATE notification_outbox
+SET state           = 'Sending',
+    next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $2)
+WHERE digest_id = $1
+  AND state IN ('Pending', 'Sending');
@@ -0,0 +1,3 @@
+DELETE
+FROM notification_digest_settings
+WHERE discord_user_id = $1;
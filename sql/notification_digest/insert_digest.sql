@@ -0,0 +1,3 @@
+INSERT INTO notification_digests(discord_user_id)
+VALUES ($1)
+RETURNING id;
@@ -0,0 +1,6 @@
+UPDATE notification_outbox
+SET state      = 'Failed',
+    attempts   = $2,
+    last_error = $3
+WHERE digest_id = $1
+  AND state IN ('Pending', 'Sending');
@@ -0,0 +1,7 @@
+UPDATE notification_outbox
+SET state           = 'Pending',
+    attempts        = $2,
+    next_attempt_at = $3,
+    last_error      = $4
+WHERE digest_id = $1
+  AND state IN ('Pending', 'Sending');
@@ -0,0 +1,5 @@
+UPDATE notification_outbox
+SET state   = 'Sent',
+    sent_at = CURRENT_TIMESTAMP
+WHERE digest_id = $1
+  AND state IN ('Pending', 'Sending');
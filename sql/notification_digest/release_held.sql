@@ -0,0 +1,8 @@
+-- Messages still waiting for a digest go out one by one
+UPDATE notification_outbox
+SET digest          = false,
+    next_attempt_at = CURRENT_TIMESTAMP
+WHERE digest
+  AND digest_id IS NULL
+  AND state = 'Pending'
+  AND character_id IN (SELECT character_id FROM eve_character_info WHERE discord_user_id = $1);
@@ -0,0 +1,11 @@
+-- Digests without a window are due once the collection run that filled them is done
+UPDATE notification_outbox o
+SET next_attempt_at = CURRENT_TIMESTAMP
+FROM eve_character_info c
+         JOIN notification_digest_settings d ON d.discord_user_id = c.discord_user_id
+WHERE o.character_id = c.character_id
+  AND d.window_minutes IS NULL
+  AND o.digest
+  AND o.digest_id IS NULL
+  AND o.state = 'Pending'
+  AND o.next_attempt_at > CURRENT_TIMESTAMP;
@@ -0,0 +1,3 @@
+SELECT discord_user_id
+FROM notification_digests
+WHERE id = $1;
@@ -0,0 +1,6 @@
+SELECT DISTINCT c.discord_user_id
+FROM notification_outbox o
+         JOIN eve_character_info c ON c.character_id = o.character_id
+WHERE o.digest
+  AND o.state IN ('Pending', 'Sending')
+  AND o.next_attempt_at <= CURRENT_TIMESTAMP;
@@ -0,0 +1,10 @@
+SELECT o.id,
+       o.raffle_id as "raffle_id!",
+       o.event_kind as "event_kind: NotificationEventKind",
+       c.character_id,
+       c.character_name,
+       o.attempts
+FROM notification_outbox o
+         JOIN eve_character_info c ON c.character_id = o.character_id
+WHERE o.digest_id = $1
+ORDER BY o.id;
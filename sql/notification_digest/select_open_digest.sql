@@ -0,0 +1,7 @@
+-- A digest that failed to send earlier and still has messages waiting
+SELECT max(o.digest_id)
+FROM notification_outbox o
+         JOIN eve_character_info c ON c.character_id = o.character_id
+WHERE c.discord_user_id = $1
+  AND o.digest
+  AND o.state IN ('Pending', 'Sending');
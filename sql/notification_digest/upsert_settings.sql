@@ -0,0 +1,4 @@
+INSERT INTO notification_digest_settings(discord_user_id, window_minutes)
+VALUES ($1, $2)
+ON CONFLICT (discord_user_id) DO UPDATE SET window_minutes = excluded.window_minutes,
+                                            updated_at     = CURRENT_TIMESTAMP;
@@ -0,0 +1,26 @@
+-- Claims the due messages for one run of the sender, other senders skip them. Messages whose
+-- claim ran out are due again.
+UPDATE notification_outbox
+SET state           = 'Sending',
+    next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $2)
+WHERE id IN (SELECT id
+             FROM notification_outbox
+             WHERE state IN ('Pending', 'Sending')
+               AND next_attempt_at <= CURRENT_TIMESTAMP
+               AND NOT digest
+             ORDER BY id
+             LIMIT $1 FOR UPDATE SKIP LOCKED)
+RETURNING id,
+    character_id,
+    raffle_id,
+    event_kind as "event_kind: NotificationEventKind",
+    backend as "backend: NotificationBackend",
+    webhook_id,
+    channel_id,
+    payload,
+    state as "state: OutboxState",
+    attempts,
+    next_attempt_at,
+    last_error,
+    created_at,
+    sent_at;
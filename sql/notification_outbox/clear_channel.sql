@@ -0,0 +1,4 @@
+UPDATE notification_outbox
+SET channel_id = null
+WHERE channel_id = $1
+  AND state = 'Pending';
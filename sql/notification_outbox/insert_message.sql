@@ -0,0 +1,53 @@
+INSERT INTO notification_outbox(character_id, raffle_id, event_kind, backend, webhook_id, channel_id, payload, digest,
+                                next_attempt_at)
+SELECT eve_character_info.character_id,
+       $2,
+       $3,
+       coalesce(p.backend, 'DiscordChannel'),
+       w.id,
+       $5::bigint,
+       $4,
+       d.discord_user_id IS NOT NULL,
+       -- Held for the digest; one without a window is released by the collection run, this is
+       -- only the fallback
+       CASE
+           WHEN d.discord_user_id IS NULL THEN CURRENT_TIMESTAMP
+           ELSE CURRENT_TIMESTAMP + make_interval(mins => coalesce(d.window_minutes, 60)) END
+FROM eve_character_info
+         LEFT JOIN notification_backend_preferences p
+                   ON p.discord_user_id = eve_character_info.discord_user_id AND p.event_kind = $3
+         LEFT JOIN webhook_endpoints w
+                   ON p.backend = 'Webhook' AND w.discord_user_id = eve_character_info.discord_user_id
+         -- Raffle messages in Discord go into the digest, unless they update a raffle message
+         LEFT JOIN notification_digest_settings d
+                   ON d.discord_user_id = eve_character_info.discord_user_id
+                       AND coalesce(p.backend, 'DiscordChannel') = 'DiscordChannel'
+                       AND $2::text IS NOT NULL
+                       AND NOT EXISTS(SELECT 1
+                                      FROM hypernet_raffles
+                                      WHERE raffle_id = $2
+                                        AND character_id = $1
+                                        AND discord_message_id IS NOT NULL)
+WHERE eve_character_info.character_id = $1
+  -- Results are only delivered where the user asked for them, or to mark the raffle message
+  AND (p.backend IS NOT NULL OR $3 <> 'ResultSet' OR EXISTS(SELECT 1
+                                                           FROM hypernet_raffles
+                                                           WHERE raffle_id = $2
+                                                             AND character_id = $1
+                                                             AND discord_message_id IS NOT NULL))
+UNION ALL
+SELECT eve_character_info.character_id,
+       $2,
+       $3,
+       'Webhook'::notification_backend,
+       w.id,
+       null::bigint,
+       $4,
+       false,
+       CURRENT_TIMESTAMP
+FROM eve_character_info
+         JOIN notification_channel_map ncm ON ncm.discord_user_id = eve_character_info.discord_user_id
+         JOIN webhook_endpoints w ON w.guild_id = ncm.guild_id
+WHERE eve_character_info.character_id = $1
+  -- Only raffle events, the rest is personal
+  AND $2::text IS NOT NULL;
@@ -0,0 +1,5 @@
+UPDATE notification_outbox
+SET state      = 'Failed',
+    attempts   = $2,
+    last_error = $3
+WHERE id = $1;
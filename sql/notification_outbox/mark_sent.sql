@@ -0,0 +1,4 @@
+UPDATE notification_outbox
+SET state   = 'Sent',
+    sent_at = CURRENT_TIMESTAMP
+WHERE id = $1;
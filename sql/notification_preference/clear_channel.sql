@@ -0,0 +1,4 @@
+UPDATE notification_preferences
+SET channel_id = null,
+    updated_at = CURRENT_TIMESTAMP
+WHERE channel_id = $1;
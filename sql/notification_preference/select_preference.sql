@@ -0,0 +1,10 @@
+SELECT character_id,
+       event_kind as "event_kind: NotificationEventKind",
+       enabled,
+       channel_id,
+       min_value,
+       min_expected_value,
+       updated_at
+FROM notification_preferences
+WHERE character_id = $1
+  AND event_kind = $2;
@@ -0,0 +1,7 @@
+INSERT INTO notification_preferences (character_id, event_kind, enabled, channel_id, min_value, min_expected_value)
+VALUES ($1, $2, $3, $4, $5, $6)
+on conflict (character_id, event_kind) do update set enabled            = excluded.enabled,
+                                                     channel_id         = excluded.channel_id,
+                                                     min_value          = excluded.min_value,
+                                                     min_expected_value = excluded.min_expected_value,
+                                                     updated_at         = CURRENT_TIMESTAMP;
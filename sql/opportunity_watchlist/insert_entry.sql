@@ -0,0 +1,3 @@
+INSERT INTO opportunity_watchlist (type_id, market_group_id)
+VALUES ($1, $2)
+on conflict do nothing;
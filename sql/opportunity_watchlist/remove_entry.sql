@@ -0,0 +1,3 @@
+DELETE
+FROM opportunity_watchlist
+WHERE id = $1;
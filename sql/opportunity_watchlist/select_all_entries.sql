@@ -0,0 +1,3 @@
+SELECT id, type_id, market_group_id
+FROM opportunity_watchlist
+ORDER BY id;
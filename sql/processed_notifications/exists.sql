@@ -0,0 +1 @@
+SELECT EXISTS(SELECT 1 FROM processed_notifications WHERE notification_id = $1) as "exists!";
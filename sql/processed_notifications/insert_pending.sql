@@ -0,0 +1,2 @@
+INSERT INTO processed_notifications (notification_id, character_id, raffle_id, notification_type, status, state)
+VALUES ($1, $2, $3, $4, $5, 'Pending');
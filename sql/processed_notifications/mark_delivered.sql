@@ -0,0 +1,4 @@
+UPDATE processed_notifications
+SET state        = 'Delivered',
+    delivered_at = CURRENT_TIMESTAMP
+WHERE notification_id = $1;
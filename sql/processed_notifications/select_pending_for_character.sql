@@ -0,0 +1,12 @@
+SELECT notification_id,
+       character_id,
+       raffle_id,
+       notification_type,
+       status as "status: HypernetRaffleStatus",
+       state  as "state: ProcessedNotificationState",
+       created_at,
+       delivered_at
+FROM processed_notifications
+WHERE character_id = $1
+  AND state = 'Pending'
+ORDER BY created_at;
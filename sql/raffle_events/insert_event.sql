@@ -0,0 +1,3 @@
+INSERT INTO raffle_events(raffle_id, character_id, kind, status, result, notification_id, discord_user_id, details,
+                          occurred_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9);
@@ -0,0 +1,7 @@
+SELECT raffle_id,
+       max(occurred_at) as "ended_at!"
+FROM raffle_events
+WHERE character_id = $1
+  AND kind = 'StatusChanged'
+  AND status IN ('Expired', 'Finished')
+GROUP BY raffle_id;
@@ -0,0 +1,15 @@
+SELECT id,
+       raffle_id,
+       character_id,
+       kind as "kind: RaffleEventKind",
+       status as "status: HypernetRaffleStatus",
+       result as "result: HypernetRaffleResult",
+       notification_id,
+       discord_user_id,
+       details,
+       occurred_at,
+       recorded_at
+FROM raffle_events
+WHERE raffle_id = $1
+  AND character_id = $2
+ORDER BY occurred_at, id;
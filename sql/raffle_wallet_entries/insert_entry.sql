@@ -0,0 +1,3 @@
+INSERT INTO raffle_wallet_entries(journal_id, raffle_id, character_id, kind, amount, ref_type, date)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+on conflict do nothing;
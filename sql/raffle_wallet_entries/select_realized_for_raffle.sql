@@ -0,0 +1,7 @@
+SELECT coalesce(sum(amount) FILTER (WHERE amount > 0), 0)  as "isk_in!",
+       coalesce(-sum(amount) FILTER (WHERE amount < 0), 0) as "isk_out!",
+       count(*)                                            as "entries!",
+       count(*) FILTER (WHERE kind IN ('Payout', 'Refund')) as "settlements!"
+FROM raffle_wallet_entries
+WHERE raffle_id = $1
+  AND character_id = $2;
@@ -0,0 +1,9 @@
+SELECT w.raffle_id,
+       w.character_id,
+       coalesce(sum(w.amount) FILTER (WHERE w.amount > 0), 0)  as "isk_in!",
+       coalesce(-sum(w.amount) FILTER (WHERE w.amount < 0), 0) as "isk_out!",
+       count(*) FILTER (WHERE w.kind IN ('Payout', 'Refund'))   as "settlements!"
+FROM raffle_wallet_entries w
+         JOIN eve_character_info c ON c.character_id = w.character_id
+WHERE c.discord_user_id = $1
+GROUP BY w.raffle_id, w.character_id;
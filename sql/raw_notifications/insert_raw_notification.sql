@@ -0,0 +1,4 @@
+INSERT INTO raw_notifications (notification_id, character_id, notification_type, timestamp, sender_id, sender_type,
+                               text)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+on conflict do nothing;
@@ -0,0 +1,9 @@
+SELECT notification_id,
+       character_id,
+       notification_type,
+       timestamp,
+       sender_id,
+       sender_type,
+       text
+FROM raw_notifications
+ORDER BY timestamp, notification_id;
@@ -0,0 +1,5 @@
+SELECT EXISTS(SELECT 1
+              FROM wallet_transactions
+              WHERE character_id = $1
+                AND type_id = $2
+                AND date >= $3) as "exists!";
@@ -0,0 +1,3 @@
+INSERT INTO wallet_transactions(transaction_id, character_id, type_id, quantity, unit_price, is_buy, date)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+on conflict do nothing;
@@ -0,0 +1,10 @@
+SELECT transaction_id,
+       character_id,
+       type_id,
+       quantity,
+       unit_price,
+       is_buy,
+       date
+FROM wallet_transactions
+WHERE character_id = $1
+ORDER BY date, transaction_id;
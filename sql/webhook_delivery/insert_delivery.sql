@@ -0,0 +1,2 @@
+INSERT INTO webhook_deliveries (webhook_id, outbox_id, event, status_code, error, duration_ms)
+VALUES ($1, $2, $3, $4, $5, $6);
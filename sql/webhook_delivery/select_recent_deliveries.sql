@@ -0,0 +1,5 @@
+SELECT *
+FROM webhook_deliveries
+WHERE webhook_id = $1
+ORDER BY attempted_at DESC
+LIMIT $2;
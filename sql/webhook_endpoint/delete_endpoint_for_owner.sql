@@ -0,0 +1,4 @@
+DELETE
+FROM webhook_endpoints
+WHERE discord_user_id IS NOT DISTINCT FROM $1
+  AND guild_id IS NOT DISTINCT FROM $2;
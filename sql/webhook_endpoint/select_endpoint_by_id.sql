@@ -0,0 +1,3 @@
+SELECT *
+FROM webhook_endpoints
+WHERE id = $1;
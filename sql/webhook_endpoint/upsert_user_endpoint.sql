@@ -0,0 +1,5 @@
+INSERT INTO webhook_endpoints (discord_user_id, url, secret)
+VALUES ($1, $2, $3)
+on conflict (discord_user_id) do update set url        = excluded.url,
+                                            secret     = excluded.secret,
+                                            created_at = CURRENT_TIMESTAMP;
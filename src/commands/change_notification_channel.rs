@@ -1,18 +1,47 @@
 use crate::context::{Context, Error};
 use poise::CreateReply;
-use serenity::all::{ChannelId, CreateEmbed, Mentionable};
+use serenity::all::{
+    Channel, ChannelId, ChannelType, CreateEmbed, CreateForumPost, CreateMessage, Mentionable,
+    Permissions,
+};
 
+/// Name of the forum post notifications are collected in when a forum is picked.
+const FORUM_POST_NAME: &str = "Hypernet Raffles";
+
+/// Set the channel, thread or forum your notifications are posted to
 #[poise::command(slash_command)]
 pub async fn change_notification_channel(
     ctx: Context<'_>,
-    #[channel_types("Text")] channel: Option<ChannelId>,
+    #[description = "Leave empty to get direct messages"]
+    #[channel_types("Text", "News", "PublicThread", "PrivateThread", "NewsThread", "Forum")]
+    channel: Option<ChannelId>,
 ) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    // A forum is replaced by the post created in it.
+    let channel = match channel {
+        Some(channel) => match prepare_channel(ctx, channel).await? {
+            Ok(channel) => Some(channel),
+            Err(reason) => {
+                let reply = CreateReply::default().ephemeral(true).embed(
+                    CreateEmbed::new()
+                        .title("Notification Channel Not Changed")
+                        .description(reason),
+                );
+                ctx.send(reply).await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
     let user_id = ctx.author().id.get() as i64;
     let channel_id = channel.map(|c| c.get() as i64);
     sqlx::query_file!(
         "./sql/notification_channel/update_notification_channel.sql",
         user_id,
-        channel_id
+        channel_id,
+        channel.and(ctx.guild_id()).map(|g| g.get() as i64)
     )
     .execute(&ctx.data().postgres)
     .await?;
@@ -31,3 +60,109 @@ pub async fn change_notification_channel(
     ctx.send(reply).await?;
     Ok(())
 }
+
+/// Makes sure notifications can be delivered to a channel by checking the bot's permissions and
+/// posting a test message. Returns the channel to store, for a forum that is the post created
+/// in it, or why the channel can't be used.
+pub async fn prepare_channel(
+    ctx: Context<'_>,
+    channel_id: ChannelId,
+) -> Result<Result<ChannelId, String>, Error> {
+    let channel = match channel_id.to_channel(ctx).await {
+        Ok(Channel::Guild(channel)) => channel,
+        Ok(_) => return Ok(Err("Only server channels can be used.".to_string())),
+        Err(e) => {
+            return Ok(Err(format!(
+                "I can't see {}, give me the View Channel permission there. ({})",
+                channel_id.mention(),
+                e
+            )))
+        }
+    };
+
+    let is_thread = matches!(
+        channel.kind,
+        ChannelType::PublicThread | ChannelType::PrivateThread | ChannelType::NewsThread
+    );
+    let mut required = Permissions::VIEW_CHANNEL | Permissions::EMBED_LINKS;
+    required |= if is_thread {
+        Permissions::SEND_MESSAGES_IN_THREADS
+    } else {
+        Permissions::SEND_MESSAGES
+    };
+
+    let member = channel.guild_id.member(ctx, ctx.framework().bot_id).await?;
+    // Threads inherit the permissions of the channel they were started in.
+    let permissions = {
+        let Some(guild) = ctx.cache().guild(channel.guild_id) else {
+            return Ok(Err(
+                "I don't know that server yet, try again in a moment.".to_string()
+            ));
+        };
+        let base = if is_thread {
+            channel
+                .parent_id
+                .and_then(|x| guild.channels.get(&x).cloned())
+        } else {
+            Some(channel.clone())
+        };
+        base.map(|x| guild.user_permissions_in(&x, &member))
+    };
+    let Some(permissions) = permissions else {
+        return Ok(Err(format!(
+            "I can't find the channel {} belongs to.",
+            channel.mention()
+        )));
+    };
+
+    let missing = required - permissions;
+    if !missing.is_empty() {
+        return Ok(Err(format!(
+            "I'm missing these permissions in {}: {}.",
+            channel.mention(),
+            missing
+        )));
+    }
+    if let Some(metadata) = channel.thread_metadata {
+        if metadata.locked && !permissions.manage_threads() {
+            return Ok(Err(format!(
+                "{} is locked, unlock it or pick another channel.",
+                channel.mention()
+            )));
+        }
+    }
+
+    let test = CreateMessage::new().embed(
+        CreateEmbed::new()
+            .title("Notification Channel Test")
+            .description(format!(
+                "Hypernet notifications of {} will be posted here.",
+                ctx.author().mention()
+            )),
+    );
+    if channel.kind == ChannelType::Forum {
+        return Ok(
+            match channel
+                .id
+                .create_forum_post(ctx, CreateForumPost::new(FORUM_POST_NAME, test))
+                .await
+            {
+                Ok(post) => Ok(post.id),
+                Err(e) => Err(format!(
+                    "I couldn't create a post in {}: {}",
+                    channel.mention(),
+                    e
+                )),
+            },
+        );
+    }
+
+    Ok(match channel.id.send_message(ctx, test).await {
+        Ok(_) => Ok(channel.id),
+        Err(e) => Err(format!(
+            "I couldn't post a test message in {}: {}",
+            channel.mention(),
+            e
+        )),
+    })
+}
@@ -0,0 +1,143 @@
+use crate::context::{Context, Error};
+use crate::hypernet::fill_model::FillEstimator;
+use crate::hypernet::prices::MarketPrices;
+use crate::hypernet::profit::ProfitType::{Loser, Winner};
+use crate::hypernet::profit::{
+    calculate_profit, expected_value, fill_weighted_expected_value, win_probability, RaffleSetup,
+};
+use crate::hypernet::types::{resolve_type, search_types};
+use poise::CreateReply;
+use serenity::all::{AutocompleteChoice, CreateEmbed};
+use thousands::Separable;
+
+pub async fn autocomplete_item(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    search_types(&ctx.data().postgres, &ctx.data().esi, partial)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| AutocompleteChoice::new(x.name, x.type_id.to_string()))
+        .collect()
+}
+
+/// Evaluate the profitability of a raffle before listing it
+#[poise::command(slash_command)]
+pub async fn evaluate(
+    ctx: Context<'_>,
+    #[description = "Item to raffle"]
+    #[autocomplete = "autocomplete_item"]
+    item: String,
+    #[description = "Number of tickets"]
+    #[min = 1]
+    tickets: i32,
+    #[description = "Price per ticket in ISK"]
+    #[min = 0]
+    price: f64,
+    #[description = "Tickets you plan to buy yourself. Defaults to half of them"]
+    #[min = 0]
+    my_share: Option<i32>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let esi = &ctx.data().esi;
+    let Some(item) = resolve_type(&ctx.data().postgres, esi, &item).await? else {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content("Unknown item."),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let mut prices = MarketPrices::fetch(esi, ctx.data().market_hub).await?;
+    let (sell_price, buy_price) = prices.item_prices(esi, item.type_id).await?;
+
+    let setup = RaffleSetup {
+        ticket_count: tickets,
+        ticket_price: price,
+        owned_tickets: my_share.map(|x| x.min(tickets)),
+        buy_price,
+        hypercore_sell_price: prices.hypercore_sell_price,
+        plex_price: prices.plex_price,
+    };
+
+    let fill_probability = match sell_price.or(buy_price) {
+        Some(reference_price) => Some(
+            FillEstimator::load(&ctx.data().postgres)
+                .await?
+                .fill_probability(item.type_id, &setup, reference_price),
+        ),
+        None => None,
+    };
+
+    let reply = CreateReply::default().ephemeral(true).embed(
+        CreateEmbed::new()
+            .title("Hypernet Raffle Evaluation")
+            .thumbnail(format!(
+                "https://images.evetech.net/types/{}/icon",
+                item.type_id
+            ))
+            .field("Item", item.name, true)
+            .field("Marked Value (Sell)", format_isk(sell_price), true)
+            .field("Marked Value (Buy)", format_isk(buy_price), true)
+            .field("Ticket Count", tickets.separate_with_dots(), true)
+            .field("Ticket Price", price.separate_with_dots(), true)
+            .field(
+                "Your Tickets",
+                setup.owned_tickets().separate_with_dots(),
+                true,
+            )
+            .field(
+                "Win Chance",
+                format!("{:.1}%", win_probability(&setup) * 100.0),
+                true,
+            )
+            .field("Payout", setup.payout().round().separate_with_dots(), true)
+            .field(
+                "Hypercores",
+                setup
+                    .required_cores()
+                    .map(|x| x.separate_with_dots())
+                    .unwrap_or("Unknown".to_string()),
+                true,
+            )
+            .field("Hypercore Cost", format_isk(setup.hypercore_cost()), true)
+            .field(
+                "Profit (Win)",
+                format_isk(calculate_profit(&setup, Winner)),
+                true,
+            )
+            .field(
+                "Profit (Lose)",
+                format_isk(calculate_profit(&setup, Loser)),
+                true,
+            )
+            .field("Expected Value", format_isk(expected_value(&setup)), true)
+            .field(
+                "Sell-out Chance",
+                fill_probability
+                    .map(|x| format!("{:.1}%", x * 100.0))
+                    .unwrap_or("Unknown".to_string()),
+                true,
+            )
+            .field(
+                "Expected Value (incl. Expiry)",
+                format_isk(fill_probability.and_then(|x| fill_weighted_expected_value(&setup, x))),
+                true,
+            )
+            .field(
+                "Break-even Ticket Price",
+                format_isk(setup.break_even_ticket_price()),
+                true,
+            ),
+    );
+
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+fn format_isk(value: Option<f64>) -> String {
+    value
+        .map(|x| x.round().separate_with_dots())
+        .unwrap_or("Unknown".to_string())
+}
@@ -1,4 +1,15 @@
 pub mod auth;
 pub mod change_notification_channel;
+pub mod evaluate;
 pub mod help;
+pub mod notification_backends;
+pub mod notification_digest;
+pub mod notification_settings;
+pub mod plan;
+pub mod raffle;
 pub mod register;
+pub mod reprocess_raffles;
+pub mod set_tickets;
+pub mod stats;
+pub mod watchlist;
+pub mod webhook;
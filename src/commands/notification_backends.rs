@@ -0,0 +1,94 @@
+use crate::context::{Context, Error};
+use crate::database::notification_preference::{
+    NotificationBackend, NotificationBackendPreference, NotificationEventKind,
+};
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+
+/// Choose how you are notified about each kind of event
+#[poise::command(slash_command, subcommands("set", "list"))]
+pub async fn notification_backends(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Turn a backend on or off for an event
+#[poise::command(slash_command)]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "Kind of event"] event: NotificationEventKind,
+    #[description = "Where to deliver it"] backend: NotificationBackend,
+    #[description = "Deliver the event through this backend"] enabled: bool,
+) -> Result<(), Error> {
+    let user_id = ctx.author().id.get() as i64;
+    if enabled {
+        sqlx::query_file!(
+            "./sql/notification_backend/insert_backend.sql",
+            user_id,
+            event as NotificationEventKind,
+            backend as NotificationBackend
+        )
+        .execute(&ctx.data().postgres)
+        .await?;
+    } else {
+        sqlx::query_file!(
+            "./sql/notification_backend/delete_backend.sql",
+            user_id,
+            event as NotificationEventKind,
+            backend as NotificationBackend
+        )
+        .execute(&ctx.data().postgres)
+        .await?;
+    }
+
+    let mut description = format!(
+        "{} is {} for {}.",
+        backend,
+        if enabled { "enabled" } else { "disabled" },
+        event
+    );
+    if enabled && backend == NotificationBackend::Webhook {
+        description.push_str(" Register the URL with /webhook set.");
+    }
+    ctx.send(
+        CreateReply::default().ephemeral(true).embed(
+            CreateEmbed::new()
+                .title("Notification Backends Changed")
+                .description(description),
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Show the backends of every event
+#[poise::command(slash_command)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let preferences: Vec<NotificationBackendPreference> = sqlx::query_file_as!(
+        NotificationBackendPreference,
+        "./sql/notification_backend/select_backends_for_user.sql",
+        ctx.author().id.get() as i64
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?;
+
+    let mut embed = CreateEmbed::new().title("Notification Backends");
+    for event in NotificationEventKind::ALL {
+        let backends: Vec<String> = preferences
+            .iter()
+            .filter(|x| x.event_kind == event)
+            .map(|x| x.backend.to_string())
+            .collect();
+        let value = if backends.is_empty() && event == NotificationEventKind::ResultSet {
+            "None (default)".to_string()
+        } else if backends.is_empty() {
+            format!("{} (default)", NotificationBackend::DiscordChannel)
+        } else {
+            backends.join(", ")
+        };
+        embed = embed.field(event.to_string(), value, false);
+    }
+
+    ctx.send(CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use crate::context::{Context, Error};
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+
+/// Bundle your raffle messages into one digest
+#[poise::command(slash_command, subcommands("enable", "disable"))]
+pub async fn notification_digest(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Send raffle messages as one digest instead of one message each
+#[poise::command(slash_command)]
+pub async fn enable(
+    ctx: Context<'_>,
+    #[description = "Minutes to collect events for, without it a digest follows every collection run"]
+    #[min = 10]
+    #[max = 1440]
+    window: Option<i32>,
+) -> Result<(), Error> {
+    sqlx::query_file!(
+        "./sql/notification_digest/upsert_settings.sql",
+        ctx.author().id.get() as i64,
+        window
+    )
+    .execute(&ctx.data().postgres)
+    .await?;
+
+    let when = match window {
+        Some(minutes) => format!("every {} minutes with events", minutes),
+        None => "after every collection run with events".to_string(),
+    };
+    ctx.send(
+        CreateReply::default().ephemeral(true).embed(
+            CreateEmbed::new()
+                .title("Notification Digest Enabled")
+                .description(format!(
+                    "Raffle messages for Discord are bundled into one digest, sent {} to your \
+                     notification channel. Pick the result of finished raffles from its menu.",
+                    when
+                )),
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Send every raffle message on its own again
+#[poise::command(slash_command)]
+pub async fn disable(ctx: Context<'_>) -> Result<(), Error> {
+    let user_id = ctx.author().id.get() as i64;
+    let mut transaction = ctx.data().postgres.begin().await?;
+    sqlx::query_file!("./sql/notification_digest/delete_settings.sql", user_id)
+        .execute(&mut *transaction)
+        .await?;
+    sqlx::query_file!("./sql/notification_digest/release_held.sql", user_id)
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+
+    ctx.send(
+        CreateReply::default().ephemeral(true).embed(
+            CreateEmbed::new()
+                .title("Notification Digest Disabled")
+                .description("Raffle messages are sent one by one again, including the ones held for the next digest."),
+        ),
+    )
+    .await?;
+    Ok(())
+}
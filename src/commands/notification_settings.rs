@@ -0,0 +1,458 @@
+use crate::commands::change_notification_channel::prepare_channel;
+use crate::context::{Context, Error};
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::notification_preference::{NotificationEventKind, NotificationPreference};
+use poise::serenity_prelude as serenity;
+use poise::{CreateReply, Modal};
+use serenity::{
+    ButtonStyle, ChannelType, ComponentInteractionCollector, ComponentInteractionDataKind,
+    CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateSelectMenu,
+    CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use std::time::Duration;
+use thousands::Separable;
+
+/// The settings message stops reacting after this long without input.
+const SETTINGS_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Modal)]
+#[name = "Thresholds"]
+struct ThresholdModal {
+    #[name = "Minimum item value (ISK)"]
+    #[placeholder = "e.g. 500m, empty for none"]
+    min_value: Option<String>,
+    #[name = "Minimum expected value (ISK)"]
+    #[placeholder = "e.g. 10m or -5m, empty for none"]
+    min_expected_value: Option<String>,
+}
+
+/// Choose per character which events you hear about, where, and from which value on
+#[poise::command(slash_command)]
+pub async fn notification_settings(ctx: Context<'_>) -> Result<(), Error> {
+    let characters: Vec<EvECharacterInfo> = sqlx::query_file_as!(
+        EvECharacterInfo,
+        "./sql/eve_character/select_characters_for_user.sql",
+        ctx.author().id.get() as i64
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?;
+    let Some(first) = characters.first() else {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content("Register a character with /register first."),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let prefix = format!("{}-settings-", ctx.id());
+    let in_guild = ctx.guild_id().is_some();
+    let mut character = first.clone();
+    let mut event = NotificationEventKind::Created;
+    let mut preferences = load_preferences(ctx, character.character_id).await?;
+
+    let (embed, components) = render(
+        &prefix,
+        &characters,
+        &character,
+        event,
+        &preferences,
+        in_guild,
+    );
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .ephemeral(true)
+                .embed(embed)
+                .components(components),
+        )
+        .await?;
+
+    loop {
+        let collector_prefix = prefix.clone();
+        let Some(interaction) = ComponentInteractionCollector::new(ctx)
+            .author_id(ctx.author().id)
+            .timeout(SETTINGS_TIMEOUT)
+            .filter(move |x| x.data.custom_id.starts_with(&collector_prefix))
+            .await
+        else {
+            break;
+        };
+        let action = &interaction.data.custom_id[prefix.len()..];
+        let mut preference = current(&preferences, character.character_id, event);
+        let mut answered = false;
+
+        match (action, &interaction.data.kind) {
+            ("character", ComponentInteractionDataKind::StringSelect { values }) => {
+                if let Some(selected) = characters
+                    .iter()
+                    .find(|x| values.first() == Some(&x.character_id.to_string()))
+                {
+                    character = selected.clone();
+                }
+            }
+            ("event", ComponentInteractionDataKind::StringSelect { values }) => {
+                if let Some(selected) = NotificationEventKind::ALL
+                    .into_iter()
+                    .find(|x| values.first() == Some(&format!("{:?}", x)))
+                {
+                    event = selected;
+                }
+            }
+            ("channel", ComponentInteractionDataKind::ChannelSelect { values }) => {
+                let Some(channel) = values.first() else {
+                    continue;
+                };
+                // Checking the channel can take longer than Discord waits for an answer.
+                interaction.defer(ctx).await?;
+                answered = true;
+                match prepare_channel(ctx, *channel).await? {
+                    Ok(channel) => {
+                        preference.channel_id = Some(channel.get() as i64);
+                        save(ctx, &preference).await?;
+                    }
+                    Err(reason) => {
+                        interaction
+                            .create_followup(
+                                ctx,
+                                CreateInteractionResponseFollowup::new()
+                                    .ephemeral(true)
+                                    .content(reason),
+                            )
+                            .await?;
+                    }
+                }
+            }
+            ("toggle", _) => {
+                preference.enabled = !preference.enabled;
+                save(ctx, &preference).await?;
+            }
+            ("default-channel", _) => {
+                preference.channel_id = None;
+                save(ctx, &preference).await?;
+            }
+            ("thresholds", _) => {
+                let defaults = ThresholdModal {
+                    min_value: preference.min_value.map(format_isk),
+                    min_expected_value: preference.min_expected_value.map(format_isk),
+                };
+                // The modal acknowledges the interaction, so the message is edited instead.
+                answered = true;
+                let Some(modal) = poise::execute_modal_on_component_interaction(
+                    ctx,
+                    interaction.clone(),
+                    Some(defaults),
+                    Some(SETTINGS_TIMEOUT),
+                )
+                .await?
+                else {
+                    continue;
+                };
+                let parsed = parse_isk(modal.min_value.as_deref().unwrap_or_default()).and_then(
+                    |min_value| {
+                        parse_isk(modal.min_expected_value.as_deref().unwrap_or_default())
+                            .map(|min_expected_value| (min_value, min_expected_value))
+                    },
+                );
+                match parsed {
+                    Ok((min_value, min_expected_value)) => {
+                        preference.min_value = min_value;
+                        preference.min_expected_value = min_expected_value;
+                        save(ctx, &preference).await?;
+                    }
+                    Err(input) => {
+                        ctx.send(CreateReply::default().ephemeral(true).content(format!(
+                            "`{}` is not an ISK amount. Use e.g. `1.500.000`, `12.5`, `1,5m` \
+                             or `2b`, or leave the field empty to remove the threshold.",
+                            input
+                        )))
+                        .await?;
+                        continue;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        preferences = load_preferences(ctx, character.character_id).await?;
+        let (embed, components) = render(
+            &prefix,
+            &characters,
+            &character,
+            event,
+            &preferences,
+            in_guild,
+        );
+        if answered {
+            reply
+                .edit(
+                    ctx,
+                    CreateReply::default().embed(embed).components(components),
+                )
+                .await?;
+        } else {
+            interaction
+                .create_response(
+                    ctx,
+                    CreateInteractionResponse::UpdateMessage(
+                        CreateInteractionResponseMessage::new()
+                            .embed(embed)
+                            .components(components),
+                    ),
+                )
+                .await?;
+        }
+    }
+
+    let (embed, _) = render(
+        &prefix,
+        &characters,
+        &character,
+        event,
+        &preferences,
+        in_guild,
+    );
+    reply
+        .edit(ctx, CreateReply::default().embed(embed).components(vec![]))
+        .await?;
+    Ok(())
+}
+
+async fn load_preferences(
+    ctx: Context<'_>,
+    character_id: i32,
+) -> Result<Vec<NotificationPreference>, Error> {
+    let preferences = sqlx::query_file_as!(
+        NotificationPreference,
+        "./sql/notification_preference/select_preferences_for_character.sql",
+        character_id
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?;
+    Ok(preferences)
+}
+
+async fn save(ctx: Context<'_>, preference: &NotificationPreference) -> Result<(), Error> {
+    sqlx::query_file!(
+        "./sql/notification_preference/upsert_preference.sql",
+        preference.character_id,
+        preference.event_kind as NotificationEventKind,
+        preference.enabled,
+        preference.channel_id,
+        preference.min_value,
+        preference.min_expected_value
+    )
+    .execute(&ctx.data().postgres)
+    .await?;
+    Ok(())
+}
+
+fn current(
+    preferences: &[NotificationPreference],
+    character_id: i32,
+    event: NotificationEventKind,
+) -> NotificationPreference {
+    preferences
+        .iter()
+        .find(|x| x.event_kind == event)
+        .cloned()
+        .unwrap_or_else(|| NotificationPreference::default_for(character_id, event))
+}
+
+fn render(
+    prefix: &str,
+    characters: &[EvECharacterInfo],
+    character: &EvECharacterInfo,
+    event: NotificationEventKind,
+    preferences: &[NotificationPreference],
+    in_guild: bool,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let mut embed = CreateEmbed::new()
+        .title(format!(
+            "Notification Settings of {}",
+            character.character_name
+        ))
+        .description(format!(
+            "Pick an event below to change it. Currently editing **{}**.",
+            event
+        ));
+    for kind in NotificationEventKind::ALL {
+        let preference = current(preferences, character.character_id, kind);
+        let mut lines = vec![if preference.enabled { "On" } else { "Off" }.to_string()];
+        lines.push(format!(
+            "Channel: {}",
+            preference
+                .channel_id
+                .map(|x| format!("<#{}>", x))
+                .unwrap_or("Default".to_string())
+        ));
+        if let Some(min_value) = preference.min_value {
+            lines.push(format!(
+                "Min. value: {}",
+                min_value.round().separate_with_dots()
+            ));
+        }
+        if let Some(min_expected_value) = preference.min_expected_value {
+            lines.push(format!(
+                "Min. EV: {}",
+                min_expected_value.round().separate_with_dots()
+            ));
+        }
+        embed = embed.field(kind.to_string(), lines.join("\n"), true);
+    }
+
+    let preference = current(preferences, character.character_id, event);
+    let mut components = vec![];
+    if characters.len() > 1 {
+        let options = characters
+            .iter()
+            .take(25)
+            .map(|x| {
+                CreateSelectMenuOption::new(&x.character_name, x.character_id.to_string())
+                    .default_selection(x.character_id == character.character_id)
+            })
+            .collect();
+        components.push(CreateActionRow::SelectMenu(CreateSelectMenu::new(
+            format!("{}character", prefix),
+            CreateSelectMenuKind::String { options },
+        )));
+    }
+
+    let options = NotificationEventKind::ALL
+        .into_iter()
+        .map(|x| {
+            CreateSelectMenuOption::new(x.to_string(), format!("{:?}", x))
+                .default_selection(x == event)
+        })
+        .collect();
+    components.push(CreateActionRow::SelectMenu(CreateSelectMenu::new(
+        format!("{}event", prefix),
+        CreateSelectMenuKind::String { options },
+    )));
+
+    // Channels can only be picked from within a server.
+    if in_guild {
+        components.push(CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                format!("{}channel", prefix),
+                CreateSelectMenuKind::Channel {
+                    channel_types: Some(vec![
+                        ChannelType::Text,
+                        ChannelType::News,
+                        ChannelType::PublicThread,
+                        ChannelType::PrivateThread,
+                        ChannelType::NewsThread,
+                        ChannelType::Forum,
+                    ]),
+                    default_channels: preference
+                        .channel_id
+                        .map(|x| vec![serenity::ChannelId::new(x as u64)]),
+                },
+            )
+            .placeholder(format!("Channel for {}", event)),
+        ));
+    }
+
+    components.push(CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}toggle", prefix))
+            .label(if preference.enabled {
+                "Turn Off"
+            } else {
+                "Turn On"
+            })
+            .style(if preference.enabled {
+                ButtonStyle::Danger
+            } else {
+                ButtonStyle::Success
+            }),
+        CreateButton::new(format!("{}thresholds", prefix))
+            .label("Thresholds")
+            .style(ButtonStyle::Primary),
+        CreateButton::new(format!("{}default-channel", prefix))
+            .label("Default Channel")
+            .style(ButtonStyle::Secondary)
+            .disabled(preference.channel_id.is_none()),
+    ]));
+
+    (embed, components)
+}
+
+fn format_isk(value: f64) -> String {
+    value.round().separate_with_dots()
+}
+
+/// Reads ISK amounts like `1.500.000`, `12.5`, `1,5m` or `2b`. Empty input means no threshold,
+/// unreadable input is handed back as the error.
+fn parse_isk(input: &str) -> Result<Option<f64>, String> {
+    let normalized = input.trim().to_lowercase().replace(' ', "");
+    let Some(suffix) = normalized.chars().last() else {
+        return Ok(None);
+    };
+    let (number, factor) = match suffix {
+        'k' => (&normalized[..normalized.len() - 1], 1e3),
+        'm' => (&normalized[..normalized.len() - 1], 1e6),
+        'b' => (&normalized[..normalized.len() - 1], 1e9),
+        _ => (normalized.as_str(), 1.0),
+    };
+    if number.is_empty()
+        || !number
+            .chars()
+            .all(|x| x.is_ascii_digit() || x == '.' || x == ',')
+    {
+        return Err(input.trim().to_string());
+    }
+
+    // Groups of three like `1.500.000` are thousands, a single other dot or comma is the
+    // decimal point.
+    let number = if is_grouped(number) {
+        number.replace(['.', ','], "")
+    } else {
+        number.replace(',', ".")
+    };
+    match number.parse::<f64>() {
+        Ok(value) => Ok(Some(value * factor)),
+        Err(_) => Err(input.trim().to_string()),
+    }
+}
+
+/// Whether the number is written in thousands groups with a single kind of separator.
+fn is_grouped(number: &str) -> bool {
+    let Some(separator) = number.chars().find(|x| *x == '.' || *x == ',') else {
+        return false;
+    };
+    let groups: Vec<&str> = number.split(separator).collect();
+    (1..=3).contains(&groups[0].len())
+        && groups[1..]
+            .iter()
+            .all(|x| x.len() == 3 && !x.contains(['.', ',']))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::notification_settings::parse_isk;
+
+    #[tokio::test]
+    async fn parse_isk_test() {
+        assert_eq!(parse_isk(""), Ok(None));
+        assert_eq!(parse_isk("  "), Ok(None));
+        assert_eq!(parse_isk("1500000"), Ok(Some(1_500_000.0)));
+        assert_eq!(parse_isk("1.500.000"), Ok(Some(1_500_000.0)));
+        assert_eq!(parse_isk("1,500,000"), Ok(Some(1_500_000.0)));
+        // What the threshold modal shows reads back the same.
+        assert_eq!(parse_isk("1.500"), Ok(Some(1_500.0)));
+        assert_eq!(parse_isk("12.5"), Ok(Some(12.5)));
+        assert_eq!(parse_isk("12,5"), Ok(Some(12.5)));
+        assert_eq!(parse_isk("1,5m"), Ok(Some(1_500_000.0)));
+        assert_eq!(parse_isk("1.5M"), Ok(Some(1_500_000.0)));
+        assert_eq!(parse_isk("250k"), Ok(Some(250_000.0)));
+        assert_eq!(parse_isk(" 2 b "), Ok(Some(2_000_000_000.0)));
+
+        assert_eq!(parse_isk("abc"), Err("abc".to_string()));
+        assert_eq!(parse_isk("m"), Err("m".to_string()));
+        assert_eq!(parse_isk("-5m"), Err("-5m".to_string()));
+        assert_eq!(parse_isk("1.2.3"), Err("1.2.3".to_string()));
+        assert_eq!(parse_isk("inf"), Err("inf".to_string()));
+    }
+}
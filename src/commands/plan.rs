@@ -0,0 +1,115 @@
+use crate::commands::evaluate::autocomplete_item;
+use crate::context::{Context, Error};
+use crate::hypernet::fill_model::FillEstimator;
+use crate::hypernet::planner::{plan as plan_raffle, PlanGoal};
+use crate::hypernet::prices::MarketPrices;
+use crate::hypernet::profit::RaffleSetup;
+use crate::hypernet::types::resolve_type;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use thousands::Separable;
+
+/// Recommend ticket counts and prices for raffling an item
+#[poise::command(slash_command)]
+pub async fn plan(
+    ctx: Context<'_>,
+    #[description = "Item to raffle"]
+    #[autocomplete = "autocomplete_item"]
+    item: String,
+    #[description = "Percentage of tickets you plan to buy yourself. Defaults to 50"]
+    #[min = 0]
+    #[max = 100]
+    my_share: Option<f64>,
+    #[description = "What to optimize for. Defaults to expected value"] goal: Option<PlanGoal>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let esi = &ctx.data().esi;
+    let Some(item) = resolve_type(&ctx.data().postgres, esi, &item).await? else {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content("Unknown item."),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let mut prices = MarketPrices::fetch(esi, ctx.data().market_hub).await?;
+    let (sell_price, buy_price) = prices.item_prices(esi, item.type_id).await?;
+    let Some(reference_price) = sell_price.or(buy_price) else {
+        ctx.send(CreateReply::default().ephemeral(true).content(format!(
+            "There are no orders for {} at the market hub.",
+            item.name
+        )))
+        .await?;
+        return Ok(());
+    };
+
+    let estimator = FillEstimator::load(&ctx.data().postgres).await?;
+    let base = RaffleSetup {
+        ticket_count: 0,
+        ticket_price: 0.0,
+        owned_tickets: None,
+        buy_price,
+        hypercore_sell_price: prices.hypercore_sell_price,
+        plex_price: prices.plex_price,
+    };
+    let goal = goal.unwrap_or(PlanGoal::MaxExpectedValue);
+    let candidates = plan_raffle(
+        &base,
+        reference_price,
+        my_share.map(|x| x / 100.0),
+        |setup| estimator.fill_probability(item.type_id, setup, reference_price),
+        goal,
+        10,
+    );
+
+    let mut embed = CreateEmbed::new()
+        .title(format!("Hypernet Raffle Plan for {}", item.name))
+        .description(format!(
+            "Best setups to {}, weighted by the chance that they sell out.",
+            match goal {
+                PlanGoal::MaxExpectedValue => "maximize the expected value",
+                PlanGoal::MinLoss => "minimize the loss",
+            }
+        ))
+        .thumbnail(format!(
+            "https://images.evetech.net/types/{}/icon",
+            item.type_id
+        ));
+
+    if candidates.is_empty() {
+        embed = embed.field(
+            "No setups",
+            "Missing market prices for Hypercores or PLEX.",
+            false,
+        );
+    }
+
+    for candidate in candidates {
+        embed = embed.field(
+            format!(
+                "{} × {} ISK",
+                candidate.setup.ticket_count,
+                candidate.setup.ticket_price.separate_with_dots()
+            ),
+            format!(
+                "EV: {} ({} if it sells out)\nLoss: {} ({} if it sells out)\nSells out: {:.0}%",
+                candidate
+                    .adjusted_expected_value
+                    .round()
+                    .separate_with_dots(),
+                candidate.expected_value.round().separate_with_dots(),
+                candidate.adjusted_loss.round().separate_with_dots(),
+                candidate.loss.round().separate_with_dots(),
+                candidate.fill_probability * 100.0
+            ),
+            true,
+        );
+    }
+
+    ctx.send(CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use crate::context::{Context, Error};
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::database::raffle_event::{RaffleEvent, RaffleEventKind};
+use crate::hypernet::profit::{estimated_profit, ProfitView};
+use crate::hypernet::reconcile::realized_profit;
+use crate::hypernet::types::type_name;
+use poise::CreateReply;
+use serenity::all::{CreateEmbed, CreateEmbedFooter};
+use thousands::Separable;
+
+/// Discord rejects embed descriptions longer than this.
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
+/// Show the full history of one of your raffles
+#[poise::command(slash_command)]
+pub async fn raffle(
+    ctx: Context<'_>,
+    #[description = "The RaffleID shown in the notification footer"] raffle_id: String,
+    #[description = "Your character in the raffle, if several of them are"] character: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let Some((raffle, _)) = find_user_raffle(ctx, &raffle_id, character).await? else {
+        return Ok(());
+    };
+
+    let events: Vec<RaffleEvent> = sqlx::query_file_as!(
+        RaffleEvent,
+        "./sql/raffle_events/select_events_for_raffle.sql",
+        raffle.raffle_id,
+        raffle.character_id
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?;
+
+    let realized = sqlx::query_file!(
+        "./sql/raffle_wallet_entries/select_realized_for_raffle.sql",
+        raffle.raffle_id,
+        raffle.character_id
+    )
+    .fetch_one(&ctx.data().postgres)
+    .await?;
+
+    let item_name = type_name(&ctx.data().postgres, &ctx.data().esi, raffle.type_id).await?;
+
+    let lines: Vec<String> = events.iter().map(format_event).collect();
+    let mut timeline = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let remaining = format!("\n… and {} more", lines.len() - i);
+        if timeline.len() + line.len() + remaining.len() + 1 > MAX_DESCRIPTION_LENGTH {
+            timeline += &remaining;
+            break;
+        }
+        timeline += line;
+        timeline += "\n";
+    }
+    if timeline.is_empty() {
+        timeline = "No events recorded for this raffle.".to_string();
+    }
+
+    let embed = CreateEmbed::new()
+        .title(format!("Hypernet Raffle - {}", item_name))
+        .thumbnail(format!(
+            "https://images.evetech.net/types/{}/icon",
+            raffle.type_id
+        ))
+        .description(timeline)
+        .field("Status", raffle.status.to_string(), true)
+        .field("Result", raffle.result.to_string(), true)
+        .field(
+            "Tickets",
+            format!(
+                "{} x {}",
+                raffle.ticket_count.separate_with_dots(),
+                raffle.ticket_price.separate_with_dots()
+            ),
+            true,
+        )
+        .field(
+            "Realized ISK (In / Out)",
+            format!(
+                "{} / {}",
+                realized.isk_in.round().separate_with_dots(),
+                realized.isk_out.round().separate_with_dots()
+            ),
+            true,
+        )
+        .field(
+            "Profit (Realized)",
+            realized_profit(
+                &raffle,
+                realized.isk_in,
+                realized.isk_out,
+                realized.settlements > 0,
+            )
+            .map(|x| x.round().separate_with_dots())
+            .unwrap_or("Pending".to_string()),
+            true,
+        )
+        .field(
+            "Profit (Market)",
+            estimated_profit(&raffle, ProfitView::MarkToMarket)
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Pending".to_string()),
+            true,
+        )
+        .field(
+            "Profit (Cost Basis)",
+            estimated_profit(&raffle, ProfitView::CostBasis)
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
+        .field(
+            "Item Cost Basis",
+            raffle
+                .item_cost_basis
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
+        .footer(CreateEmbedFooter::new(format!(
+            "RaffleID: {}",
+            raffle.raffle_id
+        )));
+
+    ctx.send(CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
+
+/// The caller's raffle with the given id and the character it belongs to. Replies and returns
+/// `None` when the caller has no such raffle, or several characters in it and none was picked.
+pub async fn find_user_raffle(
+    ctx: Context<'_>,
+    raffle_id: &str,
+    character: Option<String>,
+) -> Result<Option<(EvEHypernetRaffle, EvECharacterInfo)>, Error> {
+    let raffles: Vec<EvEHypernetRaffle> = sqlx::query_file_as!(
+        EvEHypernetRaffle,
+        "./sql/hypernet_raffle/select_user_raffles_by_id.sql",
+        raffle_id,
+        ctx.author().id.get() as i64,
+        character
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?;
+
+    let raffle = match raffles.as_slice() {
+        [] => {
+            ctx.send(
+                CreateReply::default()
+                    .ephemeral(true)
+                    .content("Unknown raffle."),
+            )
+            .await?;
+            return Ok(None);
+        }
+        [raffle] => raffle.clone(),
+        _ => {
+            ctx.send(CreateReply::default().ephemeral(true).content(
+                "Several of your characters are in this raffle, pick one with `character`.",
+            ))
+            .await?;
+            return Ok(None);
+        }
+    };
+
+    let character_info: EvECharacterInfo = sqlx::query_file_as!(
+        EvECharacterInfo,
+        "./sql/eve_character/select_character_by_id.sql",
+        raffle.character_id
+    )
+    .fetch_one(&ctx.data().postgres)
+    .await?;
+
+    Ok(Some((raffle, character_info)))
+}
+
+fn format_event(event: &RaffleEvent) -> String {
+    let mut line = format!("<t:{}:f> **{}**", event.occurred_at.timestamp(), event.kind);
+
+    match (event.kind, event.status, event.result) {
+        (RaffleEventKind::Created | RaffleEventKind::StatusChanged, Some(status), _) => {
+            line += &format!(" → {}", status);
+        }
+        (_, _, Some(result)) => {
+            line += &format!(" → {}", result);
+        }
+        _ => {}
+    }
+    if let Some(discord_user_id) = event.discord_user_id {
+        line += &format!(" by <@{}>", discord_user_id);
+    }
+    if let Some(details) = event.details.as_deref().and_then(format_details) {
+        line += &format!(" ({})", details);
+    }
+
+    line
+}
+
+/// Renders the JSON details of an event as `key: value` pairs.
+fn format_details(details: &str) -> Option<String> {
+    let serde_json::Value::Object(details) = serde_json::from_str(details).ok()? else {
+        return Some(details.to_string());
+    };
+
+    let parts: Vec<String> = details
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::Number(n) => n
+                    .as_f64()
+                    .map(|x| x.round().separate_with_dots())
+                    .unwrap_or(n.to_string()),
+                serde_json::Value::Null => "Unknown".to_string(),
+                other => other.to_string(),
+            };
+            format!("{}: {}", key.replace('_', " "), value)
+        })
+        .collect();
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(", "))
+    }
+}
@@ -0,0 +1,71 @@
+use crate::context::{Context, Error};
+use crate::hypernet::reprocess::reprocess_raffles as reprocess;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+
+/// Maximum number of raffle IDs or errors listed per field.
+const MAX_LISTED: usize = 10;
+
+/// Rebuild the stored raffles from the archived notifications
+#[poise::command(slash_command, owners_only)]
+pub async fn reprocess_raffles(
+    ctx: Context<'_>,
+    #[description = "Write the changes. Without this only a dry run is done"] apply: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let apply = apply.unwrap_or(false);
+    let report = reprocess(&ctx.data().postgres, apply).await?;
+
+    let embed = CreateEmbed::new()
+        .title(if apply {
+            "Raffles Reprocessed"
+        } else {
+            "Raffle Reprocessing (Dry Run)"
+        })
+        .description(format!(
+            "Parsed {} archived notifications. No Discord notifications are sent for these changes.",
+            report.notifications
+        ))
+        .field(
+            format!("Created ({})", report.created.len()),
+            list(&report.created),
+            false,
+        )
+        .field(
+            format!("Updated ({})", report.updated.len()),
+            list(&report.updated),
+            false,
+        )
+        .field(
+            format!("Status Changed ({})", report.transitioned.len()),
+            list(&report.transitioned),
+            false,
+        )
+        .field(
+            format!("Parse Errors ({})", report.errors.len()),
+            list(&report.errors),
+            false,
+        );
+
+    ctx.send(CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
+
+fn list(items: &[String]) -> String {
+    if items.is_empty() {
+        return "None".to_string();
+    }
+
+    let mut listed = items
+        .iter()
+        .take(MAX_LISTED)
+        .map(|x| format!("`{}`", x))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if items.len() > MAX_LISTED {
+        listed += &format!("\n… and {} more", items.len() - MAX_LISTED);
+    }
+    listed
+}
@@ -0,0 +1,75 @@
+use crate::commands::raffle::find_user_raffle;
+use crate::context::{Context, Error};
+use crate::database::hypernet_raffle_model::{HypernetRaffleResult, HypernetRaffleStatus};
+use crate::database::raffle_event::RaffleEventKind;
+use chrono::Utc;
+use poise::CreateReply;
+use serde_json::json;
+use serenity::all::CreateEmbed;
+use thousands::Separable;
+
+/// Set how many tickets your character holds in one of its raffles
+#[poise::command(slash_command)]
+pub async fn set_tickets(
+    ctx: Context<'_>,
+    #[description = "The RaffleID shown in the notification footer"] raffle_id: String,
+    #[description = "Number of tickets your character bought"]
+    #[min = 0]
+    tickets: i32,
+    #[description = "Your character in the raffle, if several of them are"] character: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    let Some((raffle, character_info)) = find_user_raffle(ctx, &raffle_id, character).await? else {
+        return Ok(());
+    };
+
+    if tickets > raffle.ticket_count {
+        ctx.send(CreateReply::default().ephemeral(true).content(format!(
+            "This raffle only has {} tickets.",
+            raffle.ticket_count
+        )))
+        .await?;
+        return Ok(());
+    }
+
+    let mut transaction = ctx.data().postgres.begin().await?;
+    sqlx::query_file!(
+        "./sql/hypernet_raffle/update_owned_tickets.sql",
+        raffle.raffle_id,
+        raffle.character_id,
+        tickets
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query_file!(
+        "./sql/raffle_events/insert_event.sql",
+        raffle.raffle_id,
+        raffle.character_id,
+        RaffleEventKind::Correction as RaffleEventKind,
+        None as Option<HypernetRaffleStatus>,
+        None as Option<HypernetRaffleResult>,
+        None as Option<i64>,
+        Some(ctx.author().id.get() as i64),
+        Some(json!({ "owned_tickets": tickets }).to_string()),
+        Utc::now(),
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+
+    let reply = CreateReply::default().ephemeral(true).embed(
+        CreateEmbed::new()
+            .title("Tickets Updated.")
+            .description(format!(
+                "{} now holds {} of {} tickets in raffle {}.",
+                character_info.character_name,
+                tickets.separate_with_dots(),
+                raffle.ticket_count.separate_with_dots(),
+                raffle.raffle_id
+            )),
+    );
+
+    ctx.send(reply).await?;
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use crate::context::{Context, Error};
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::hypernet::profit::{estimated_profit, ProfitView};
+use crate::hypernet::reconcile::realized_profit;
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+use std::collections::HashMap;
+use thousands::Separable;
+
+/// Show statistics for the raffles you created and the ones you bought tickets in
+#[poise::command(slash_command)]
+pub async fn stats(ctx: Context<'_>) -> Result<(), Error> {
+    let rows = sqlx::query_file!(
+        "./sql/hypernet_raffle/select_stats_for_user.sql",
+        ctx.author().id.get() as i64
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?;
+
+    if rows.is_empty() {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content("No raffles tracked for your characters yet."),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let raffles: Vec<EvEHypernetRaffle> = sqlx::query_file_as!(
+        EvEHypernetRaffle,
+        "./sql/hypernet_raffle/select_raffles_for_user.sql",
+        ctx.author().id.get() as i64
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?;
+    let realized: HashMap<(String, i32), (f64, f64, i64)> = sqlx::query_file!(
+        "./sql/raffle_wallet_entries/select_realized_for_user.sql",
+        ctx.author().id.get() as i64
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?
+    .into_iter()
+    .map(|x| {
+        (
+            (x.raffle_id, x.character_id),
+            (x.isk_in, x.isk_out, x.settlements),
+        )
+    })
+    .collect();
+
+    let mut embed = CreateEmbed::new().title("Hypernet Statistics");
+    for row in rows {
+        // Both profits over the raffles whose wallet entries we matched, so they are comparable.
+        let (reconciled, realized_total, estimated_total) = raffles
+            .iter()
+            .filter(|x| x.role == row.role)
+            .filter_map(|x| {
+                let (isk_in, isk_out, settlements) =
+                    realized.get(&(x.raffle_id.clone(), x.character_id))?;
+                Some((
+                    realized_profit(x, *isk_in, *isk_out, *settlements > 0)?,
+                    estimated_profit(x, ProfitView::MarkToMarket)?,
+                ))
+            })
+            .fold((0, 0.0, 0.0), |(count, realized, estimated), (r, e)| {
+                (count + 1, realized + r, estimated + e)
+            });
+        let mut profit = format!(
+            "\nReconciled Raffles: {}\nProfit (Realized): {}\nProfit (Estimated): {}",
+            reconciled.separate_with_dots(),
+            realized_total.round().separate_with_dots(),
+            estimated_total.round().separate_with_dots()
+        );
+
+        if row.role == HypernetRaffleRole::Owner {
+            // Market and cost-basis view over the raffles whose purchases we could match.
+            let (matched, market_total, cost_basis_total) = raffles
+                .iter()
+                .filter(|x| x.role == row.role)
+                .filter_map(|x| {
+                    Some((
+                        estimated_profit(x, ProfitView::MarkToMarket)?,
+                        estimated_profit(x, ProfitView::CostBasis)?,
+                    ))
+                })
+                .fold((0, 0.0, 0.0), |(count, market, cost_basis), (m, c)| {
+                    (count + 1, market + m, cost_basis + c)
+                });
+            profit += &format!(
+                "\nRaffles with Cost Basis: {}\nProfit (Market): {}\nProfit (Cost Basis): {}",
+                matched.separate_with_dots(),
+                market_total.round().separate_with_dots(),
+                cost_basis_total.round().separate_with_dots()
+            );
+        }
+
+        let (title, body) = match row.role {
+            HypernetRaffleRole::Owner => (
+                "Created Raffles",
+                format!(
+                    "Raffles: {}\nWon Back: {}\nSold: {}\nExpired: {}\nOwn Tickets: {}\nISK Spent on Tickets: {}",
+                    row.raffles.separate_with_dots(),
+                    row.won.separate_with_dots(),
+                    row.lost.separate_with_dots(),
+                    row.expired.separate_with_dots(),
+                    row.tickets.separate_with_dots(),
+                    row.isk_spent.round().separate_with_dots(),
+                ) + &profit,
+            ),
+            HypernetRaffleRole::Participant => (
+                "Joined Raffles",
+                format!(
+                    "Raffles: {}\nWon: {}\nLost: {}\nExpired: {}\nTickets Bought: {}\nISK Spent: {}\nValue Won: {}",
+                    row.raffles.separate_with_dots(),
+                    row.won.separate_with_dots(),
+                    row.lost.separate_with_dots(),
+                    row.expired.separate_with_dots(),
+                    row.tickets.separate_with_dots(),
+                    row.isk_spent.round().separate_with_dots(),
+                    row.value_won.round().separate_with_dots(),
+                ) + &profit,
+            ),
+        };
+        embed = embed.field(title, body, true);
+    }
+
+    ctx.send(CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
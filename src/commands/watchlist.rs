@@ -0,0 +1,135 @@
+use crate::commands::evaluate::autocomplete_item;
+use crate::context::{Context, Error};
+use crate::database::opportunity_watchlist::OpportunityWatchlistEntry;
+use crate::esi::market::get_market_group;
+use crate::hypernet::types::{resolve_type, type_name};
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+
+/// Manage the items the opportunity scanner checks
+#[poise::command(
+    slash_command,
+    owners_only,
+    subcommands("add_item", "add_group", "remove", "list")
+)]
+pub async fn watchlist(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Add an item to the watchlist
+#[poise::command(slash_command, owners_only)]
+pub async fn add_item(
+    ctx: Context<'_>,
+    #[description = "Item to watch"]
+    #[autocomplete = "autocomplete_item"]
+    item: String,
+) -> Result<(), Error> {
+    let Some(item) = resolve_type(&ctx.data().postgres, &ctx.data().esi, &item).await? else {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content("Unknown item."),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    sqlx::query_file!(
+        "./sql/opportunity_watchlist/insert_entry.sql",
+        Some(item.type_id),
+        None::<i32>
+    )
+    .execute(&ctx.data().postgres)
+    .await?;
+
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(format!("Added {} to the watchlist.", item.name)),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Add all items of a market group to the watchlist
+#[poise::command(slash_command, owners_only)]
+pub async fn add_group(
+    ctx: Context<'_>,
+    #[description = "ID of the market group"] market_group_id: i32,
+) -> Result<(), Error> {
+    let group = get_market_group(&ctx.data().esi, market_group_id).await?;
+
+    sqlx::query_file!(
+        "./sql/opportunity_watchlist/insert_entry.sql",
+        None::<i32>,
+        Some(market_group_id)
+    )
+    .execute(&ctx.data().postgres)
+    .await?;
+
+    ctx.send(CreateReply::default().ephemeral(true).content(format!(
+        "Added market group {} ({} items) to the watchlist.",
+        group.name,
+        group.types.len()
+    )))
+    .await?;
+    Ok(())
+}
+
+/// Remove an entry from the watchlist
+#[poise::command(slash_command, owners_only)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "ID of the entry as shown by /watchlist list"] id: i32,
+) -> Result<(), Error> {
+    let result = sqlx::query_file!("./sql/opportunity_watchlist/remove_entry.sql", id)
+        .execute(&ctx.data().postgres)
+        .await?;
+
+    let content = if result.rows_affected() > 0 {
+        "Removed the entry from the watchlist."
+    } else {
+        "Unknown watchlist entry."
+    };
+    ctx.send(CreateReply::default().ephemeral(true).content(content))
+        .await?;
+    Ok(())
+}
+
+/// Show the watchlist
+#[poise::command(slash_command, owners_only)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let entries: Vec<OpportunityWatchlistEntry> = sqlx::query_file_as!(
+        OpportunityWatchlistEntry,
+        "./sql/opportunity_watchlist/select_all_entries.sql"
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?;
+
+    let mut lines = vec![];
+    for entry in entries {
+        if let Some(type_id) = entry.type_id {
+            let name = type_name(&ctx.data().postgres, &ctx.data().esi, type_id).await?;
+            lines.push(format!("`{}` Item: {}", entry.id, name));
+        }
+        if let Some(market_group_id) = entry.market_group_id {
+            lines.push(format!("`{}` Market group: {}", entry.id, market_group_id));
+        }
+    }
+
+    let description = if lines.is_empty() {
+        "The watchlist is empty.".to_string()
+    } else {
+        lines.join("\n")
+    };
+
+    ctx.send(
+        CreateReply::default().ephemeral(true).embed(
+            CreateEmbed::new()
+                .title("Opportunity Watchlist")
+                .description(description),
+        ),
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use crate::context::{Context, Error};
+use crate::database::webhook_endpoint::{WebhookDelivery, WebhookEndpoint};
+use crate::notify::{deliver_test, resolve_public_url, EVENT_HEADER, SIGNATURE_HEADER};
+use poise::CreateReply;
+use serenity::all::CreateEmbed;
+
+/// Deliveries shown by the deliveries subcommands.
+const RECENT_DELIVERIES: i64 = 10;
+
+/// Manage the webhook your notifications are posted to
+#[poise::command(slash_command, subcommands("set", "remove", "test", "deliveries"))]
+pub async fn webhook(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Register a webhook URL, a new signing secret is generated
+#[poise::command(slash_command)]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "HTTPS URL the events are posted to"] url: String,
+) -> Result<(), Error> {
+    register(ctx, Owner::User(ctx.author().id.get() as i64), url).await
+}
+
+/// Remove your webhook
+#[poise::command(slash_command)]
+pub async fn remove(ctx: Context<'_>) -> Result<(), Error> {
+    unregister(ctx, Owner::User(ctx.author().id.get() as i64)).await
+}
+
+/// Send a test event to your webhook
+#[poise::command(slash_command)]
+pub async fn test(ctx: Context<'_>) -> Result<(), Error> {
+    send_test(ctx, Owner::User(ctx.author().id.get() as i64)).await
+}
+
+/// Show the latest deliveries to your webhook
+#[poise::command(slash_command)]
+pub async fn deliveries(ctx: Context<'_>) -> Result<(), Error> {
+    list_deliveries(ctx, Owner::User(ctx.author().id.get() as i64)).await
+}
+
+/// Manage the webhook receiving the raffle events of everyone notified in this server
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("server_set", "server_remove", "server_test", "server_deliveries")
+)]
+pub async fn server_webhook(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Register the server webhook URL, a new signing secret is generated
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    rename = "set"
+)]
+pub async fn server_set(
+    ctx: Context<'_>,
+    #[description = "HTTPS URL the events are posted to"] url: String,
+) -> Result<(), Error> {
+    register(ctx, guild_owner(ctx)?, url).await
+}
+
+/// Remove the server webhook
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    rename = "remove"
+)]
+pub async fn server_remove(ctx: Context<'_>) -> Result<(), Error> {
+    unregister(ctx, guild_owner(ctx)?).await
+}
+
+/// Send a test event to the server webhook
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    rename = "test"
+)]
+pub async fn server_test(ctx: Context<'_>) -> Result<(), Error> {
+    send_test(ctx, guild_owner(ctx)?).await
+}
+
+/// Show the latest deliveries to the server webhook
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    rename = "deliveries"
+)]
+pub async fn server_deliveries(ctx: Context<'_>) -> Result<(), Error> {
+    list_deliveries(ctx, guild_owner(ctx)?).await
+}
+
+/// Who a webhook belongs to.
+#[derive(Debug, Clone, Copy)]
+enum Owner {
+    User(i64),
+    Guild(i64),
+}
+
+impl Owner {
+    fn discord_user_id(&self) -> Option<i64> {
+        match self {
+            Owner::User(id) => Some(*id),
+            Owner::Guild(_) => None,
+        }
+    }
+
+    fn guild_id(&self) -> Option<i64> {
+        match self {
+            Owner::User(_) => None,
+            Owner::Guild(id) => Some(*id),
+        }
+    }
+}
+
+fn guild_owner(ctx: Context<'_>) -> Result<Owner, Error> {
+    let guild_id = ctx.guild_id().ok_or("Only available in servers")?;
+    Ok(Owner::Guild(guild_id.get() as i64))
+}
+
+async fn endpoint(ctx: Context<'_>, owner: Owner) -> Result<Option<WebhookEndpoint>, Error> {
+    let endpoint = sqlx::query_file_as!(
+        WebhookEndpoint,
+        "./sql/webhook_endpoint/select_endpoint_for_owner.sql",
+        owner.discord_user_id(),
+        owner.guild_id()
+    )
+    .fetch_optional(&ctx.data().postgres)
+    .await?;
+    Ok(endpoint)
+}
+
+async fn register(ctx: Context<'_>, owner: Owner, url: String) -> Result<(), Error> {
+    if let Err(e) = resolve_public_url(&url).await {
+        ctx.send(CreateReply::default().ephemeral(true).content(format!(
+            "The webhook URL has to start with https:// and point to a public address: {}",
+            e
+        )))
+        .await?;
+        return Ok(());
+    }
+
+    let secret = hex::encode(rand::random::<[u8; 32]>());
+    match owner {
+        Owner::User(discord_user_id) => {
+            sqlx::query_file!(
+                "./sql/webhook_endpoint/upsert_user_endpoint.sql",
+                discord_user_id,
+                url,
+                secret
+            )
+            .execute(&ctx.data().postgres)
+            .await?;
+        }
+        Owner::Guild(guild_id) => {
+            sqlx::query_file!(
+                "./sql/webhook_endpoint/upsert_guild_endpoint.sql",
+                guild_id,
+                url,
+                secret
+            )
+            .execute(&ctx.data().postgres)
+            .await?;
+        }
+    }
+
+    let events = match owner {
+        Owner::User(_) => "Pick the events to send with /notification_backends set.",
+        Owner::Guild(_) => {
+            "It receives the raffle events of everyone whose notification channel is in this \
+             server."
+        }
+    };
+    let embed = CreateEmbed::new()
+        .title("Webhook Registered")
+        .description(format!(
+            "Events are posted as JSON to {}. The `{}` header holds the kind of event and \
+             `{}` the HMAC-SHA256 of the body as `sha256=<hex>`, keyed with this secret:\n\
+             ||`{}`||\n{}",
+            url, EVENT_HEADER, SIGNATURE_HEADER, secret, events
+        ));
+    ctx.send(CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
+
+async fn unregister(ctx: Context<'_>, owner: Owner) -> Result<(), Error> {
+    sqlx::query_file!(
+        "./sql/webhook_endpoint/delete_endpoint_for_owner.sql",
+        owner.discord_user_id(),
+        owner.guild_id()
+    )
+    .execute(&ctx.data().postgres)
+    .await?;
+
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content("Webhook removed."),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn send_test(ctx: Context<'_>, owner: Owner) -> Result<(), Error> {
+    let Some(endpoint) = endpoint(ctx, owner).await? else {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content("No webhook registered."),
+        )
+        .await?;
+        return Ok(());
+    };
+    ctx.defer_ephemeral().await?;
+
+    let content = match deliver_test(&ctx.data().postgres, &endpoint).await {
+        Ok(()) => format!("Test event delivered to {}.", endpoint.url),
+        Err(e) => format!("Test event to {} failed: {}", endpoint.url, e),
+    };
+    ctx.send(CreateReply::default().ephemeral(true).content(content))
+        .await?;
+    Ok(())
+}
+
+async fn list_deliveries(ctx: Context<'_>, owner: Owner) -> Result<(), Error> {
+    let Some(endpoint) = endpoint(ctx, owner).await? else {
+        ctx.send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content("No webhook registered."),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let deliveries: Vec<WebhookDelivery> = sqlx::query_file_as!(
+        WebhookDelivery,
+        "./sql/webhook_delivery/select_recent_deliveries.sql",
+        endpoint.id,
+        RECENT_DELIVERIES
+    )
+    .fetch_all(&ctx.data().postgres)
+    .await?;
+
+    let lines: Vec<String> = deliveries
+        .iter()
+        .map(|x| {
+            format!(
+                "<t:{}:R> {} → {} ({} ms)",
+                x.attempted_at.timestamp(),
+                x.event,
+                match (&x.error, x.status_code) {
+                    (None, Some(status)) => status.to_string(),
+                    (Some(error), _) => error.clone(),
+                    (None, None) => "No answer".to_string(),
+                },
+                x.duration_ms
+            )
+        })
+        .collect();
+    let description = if lines.is_empty() {
+        "Nothing delivered yet.".to_string()
+    } else {
+        lines.join("\n")
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!("Deliveries to {}", endpoint.url))
+        .description(description);
+    ctx.send(CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
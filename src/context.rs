@@ -1,11 +1,13 @@
+use crate::hypernet::prices::MarketHub;
 use rfesi::prelude::Esi;
-use serenity::all::Http;
+use serenity::all::{Http, UserId};
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AppContext {
     pub esi: Esi,
     pub postgres: sqlx::PgPool,
+    pub market_hub: MarketHub,
 } // User data, which is stored and accessible in all command invocations
 
 #[derive(Clone)]
@@ -13,6 +15,9 @@ pub struct CronAppContext {
     pub esi: Esi,
     pub postgres: sqlx::PgPool,
     pub discord_http: Arc<Http>,
+    pub market_hub: MarketHub,
+    /// Bot owners, alerted when notifications keep failing and the user can't be told.
+    pub owners: Vec<UserId>,
 }
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
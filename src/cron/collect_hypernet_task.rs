@@ -1,21 +1,39 @@
 use crate::context::CronAppContext;
-use crate::cron::collect_hypernet_task::ProfitType::{Loser, Winner};
 use crate::cron::CronTask;
 use crate::database::eve_character_info::EvECharacterInfo;
 use crate::database::hypernet_raffle_model::{
-    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleStatus,
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
 };
-use anyhow::anyhow;
+use crate::database::notification_preference::NotificationEventKind;
+use crate::database::processed_notification::{ProcessedNotification, ProcessedNotificationState};
+use crate::database::raffle_event::RaffleEventKind;
+use crate::esi::wallet_journal::get_wallet_journal;
+use crate::hypernet::fill_model::FillEstimator;
+use crate::hypernet::notifications::{
+    is_raffle_notification, parse_raffles, RaffleNotification, RAFFLE_CREATED, RAFFLE_EXPIRED,
+    RAFFLE_FINISHED,
+};
+use crate::hypernet::prices::MarketPrices;
+use crate::hypernet::profit::ProfitType::{Loser, Winner};
+use crate::hypernet::profit::{
+    calculate_profit, estimated_profit, expected_value, fill_weighted_expected_value,
+    participant_expected_value, participant_profit, win_probability, ProfitView, RaffleSetup,
+};
+use crate::hypernet::reconcile::{derive_ticket_purchase, realized_profit, ReconcileRaffle};
+use crate::hypernet::results::held_quantity;
+use crate::hypernet::types::type_name;
+use crate::notify::{enqueue_event, NotificationEvent};
 use async_trait::async_trait;
+use chrono::Utc;
 use log::{debug, warn};
-use rfesi::groups::Notification;
 use rfesi::prelude::Esi;
+use serde_json::json;
 use serenity::all::{
-    ButtonStyle, ChannelId, Colour, CreateButton, CreateEmbed, CreateEmbedFooter, CreateMessage,
+    ButtonStyle, Colour, CreateButton, CreateEmbed, CreateEmbedFooter, CreateMessage,
 };
 use serenity::builder::CreateActionRow;
-use sqlx::{query_file, query_file_as, Executor};
-use std::collections::{HashMap, HashSet};
+use sqlx::{query_file, query_file_as, query_file_scalar, Executor, PgPool, Postgres, Transaction};
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::time::Duration;
 use thousands::Separable;
@@ -33,7 +51,7 @@ impl CronTask for CollectHypernetTask {
     }
 
     fn timeout(&self) -> Duration {
-        Duration::from_secs(60)
+        Duration::from_secs(180)
     }
 
     async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
@@ -44,58 +62,20 @@ impl CronTask for CollectHypernetTask {
         .fetch_all(&ctx.postgres)
         .await?;
 
-        // Prices for each type_id, (sell, buy)
-        let mut prices: HashMap<i32, (Option<f64>, Option<f64>)> = HashMap::new();
-        let hypernet_core_orders = loop {
-            let hypernet_core_orders = ctx
-                .esi
-                .group_market()
-                .get_region_orders(10000002, None, None, Some(52568))
-                .await;
-
-            if let Ok(orders) = hypernet_core_orders {
-                break orders;
-            } else {
-                warn!("Error fetching orders for Hypercore. Retrying...");
-            }
-        };
-
-        let hypernet_core_sell_price = hypernet_core_orders
-            .iter()
-            .filter(|x| !x.is_buy_order)
-            .map(|x| x.price)
-            .min_by(|a, b| a.partial_cmp(b).unwrap());
-
-        let hypernet_core_buy_price = hypernet_core_orders
-            .iter()
-            .filter(|x| x.is_buy_order)
-            .map(|x| x.price)
-            .max_by(|a, b| a.partial_cmp(b).unwrap());
-
-        let plex_price = ctx
-            .esi
-            .group_market()
-            .get_market_prices()
-            .await?
-            .iter()
-            .find(|x| x.type_id == 44992) // PLEX
-            .and_then(|x| x.average_price);
+        let mut prices = MarketPrices::fetch(&ctx.esi, ctx.market_hub).await?;
+        let estimator = FillEstimator::load(&ctx.postgres).await?;
 
         for char in all_chars {
-            let res = handle_character(
-                &ctx,
-                char,
-                &mut prices,
-                &hypernet_core_sell_price,
-                &hypernet_core_buy_price,
-                &plex_price,
-            )
-            .await;
+            let res = handle_character(&ctx, char, &mut prices, &estimator).await;
             if let Err(e) = res {
                 log::error!("Error handling character: {:?}", e);
             }
         }
 
+        query_file!("./sql/notification_digest/release_run.sql")
+            .execute(&ctx.postgres)
+            .await?;
+
         Ok(())
     }
 }
@@ -103,20 +83,10 @@ impl CronTask for CollectHypernetTask {
 async fn handle_character(
     ctx: &CronAppContext,
     char: EvECharacterInfo,
-    price_cache: &mut HashMap<i32, (Option<f64>, Option<f64>)>,
-    hypernet_core_sell_price: &Option<f64>,
-    hypernet_core_buy_price: &Option<f64>,
-    plex_price: &Option<f64>,
+    prices: &mut MarketPrices,
+    estimator: &FillEstimator,
 ) -> anyhow::Result<()> {
     let mut esi = ctx.esi.clone();
-    let notification_channel_id: Option<i64> = sqlx::query_file_scalar!(
-        "./sql/notification_channel/select_channel_for_user.sql",
-        char.character_id
-    )
-    .fetch_optional(&ctx.postgres)
-    .await?
-    .flatten();
-
     esi.use_refresh_token(&char.refresh_token).await?;
 
     let notifications = esi
@@ -124,228 +94,548 @@ async fn handle_character(
         .get_notifications(char.character_id)
         .await?;
 
+    // Keep the original text around, so we can reprocess it if the parser ever falls short.
+    let mut transaction = ctx.postgres.begin().await?;
+    for notification in notifications
+        .iter()
+        .filter(|n| is_raffle_notification(&n.notification_type))
+    {
+        let query = query_file!(
+            "./sql/raw_notifications/insert_raw_notification.sql",
+            notification.notification_id,
+            char.character_id,
+            notification.notification_type,
+            chrono::DateTime::<chrono::Utc>::from_str(&notification.timestamp)?,
+            notification.sender_id,
+            notification.sender_type,
+            notification.text
+        );
+        transaction.execute(query).await?;
+    }
+    transaction.commit().await?;
+
     let raffles_created: Vec<_> = notifications
         .iter()
-        .filter(|n| n.notification_type == "RaffleCreated")
+        .filter(|n| n.notification_type == RAFFLE_CREATED)
         .collect();
 
     let raffles_expired: Vec<_> = notifications
         .iter()
-        .filter(|n| n.notification_type == "RaffleExpired")
+        .filter(|n| n.notification_type == RAFFLE_EXPIRED)
         .collect();
 
     let raffles_finished: Vec<_> = notifications
         .iter()
-        .filter(|n| n.notification_type == "RaffleFinished")
+        .filter(|n| n.notification_type == RAFFLE_FINISHED)
         .collect();
 
-    let raffles_created = parse_raffles(&raffles_created, char.character_id)?;
-    let raffles_expired = parse_raffles(&raffles_expired, char.character_id)?;
-    let raffles_finished = parse_raffles(&raffles_finished, char.character_id)?;
+    let raffles_created = parse_raffles(&raffles_created, char.character_id);
+    let raffles_expired = parse_raffles(&raffles_expired, char.character_id);
+    let raffles_finished = parse_raffles(&raffles_finished, char.character_id);
 
-    // Insert new raffles
-    let mut transaction = ctx.postgres.begin().await?;
-    for raffle in raffles_created.iter().cloned() {
-        let mut prices = price_cache.get(&raffle.type_id);
-        if prices.is_none() {
-            let orders = loop {
-                let orders = esi
-                    .group_market()
-                    .get_region_orders(
-                        10000002, // The Forge
-                        None,
-                        None,
-                        Some(raffle.type_id),
-                    )
-                    .await;
-
-                if let Ok(orders) = orders {
-                    break orders;
-                } else {
-                    warn!(
-                        "Error fetching orders for type_id: {}. Retrying...",
-                        raffle.type_id
-                    );
-                }
-            };
+    // A broken notification only skips itself. It stays archived and can be picked up again
+    // with /reprocess_raffles once the parser handles it.
+    for error in raffles_created
+        .errors
+        .iter()
+        .chain(&raffles_expired.errors)
+        .chain(&raffles_finished.errors)
+    {
+        warn!(
+            "Skipping unparsable notification for character {}: {}",
+            char.character_id, error
+        );
+    }
+    for notification in raffles_created
+        .raffles
+        .iter()
+        .chain(&raffles_expired.raffles)
+        .chain(&raffles_finished.raffles)
+        .filter(|n| !n.extra.is_empty())
+    {
+        debug!(
+            "Notification {} has unknown fields: {:?}",
+            notification.notification_id,
+            notification.extra.keys().collect::<Vec<_>>()
+        );
+    }
+    let raffles_expired = raffles_expired.raffles;
+    let raffles_finished = raffles_finished.raffles;
 
-            let sell_orders = orders
-                .iter()
-                .filter(|x| !x.is_buy_order)
-                .collect::<Vec<_>>();
-            let buy_orders = orders.iter().filter(|x| x.is_buy_order).collect::<Vec<_>>();
+    let raffles_created: Vec<(i64, EvEHypernetRaffle)> = raffles_created
+        .raffles
+        .into_iter()
+        .map(|x| (x.notification_id, x.raffle))
+        .collect();
 
-            let sell_price = sell_orders
+    // ESI keeps returning notifications for days. The journal and the assets are only fetched
+    // for raffles we don't know yet or that still lack the data taken from them.
+    let raffle_ids: Vec<String> = raffles_created
+        .iter()
+        .map(|(_, x)| &x.raffle_id)
+        .chain(
+            raffles_expired
                 .iter()
-                .filter(|x| x.location_id == 60003760) // Jita 4-4
-                .map(|x| x.price)
-                .min_by(|a, b| a.partial_cmp(b).unwrap());
+                .chain(&raffles_finished)
+                .map(|x| &x.raffle.raffle_id),
+        )
+        .cloned()
+        .collect();
+    let missing: HashMap<String, (bool, bool)> = query_file!(
+        "./sql/hypernet_raffle/select_missing_wallet_data.sql",
+        char.character_id,
+        &raffle_ids
+    )
+    .fetch_all(&ctx.postgres)
+    .await?
+    .into_iter()
+    .map(|x| {
+        (
+            x.raffle_id,
+            (x.owned_tickets_missing, x.asset_baseline_missing),
+        )
+    })
+    .collect();
+
+    // Participants only hear about a raffle when it ends, so their tickets are looked up then.
+    let needs_journal = raffles_created.iter().any(|(_, x)| {
+        missing
+            .get(&x.raffle_id)
+            .is_none_or(|(tickets, _)| *tickets)
+    }) || raffles_expired.iter().chain(&raffles_finished).any(|x| {
+        missing
+            .get(&x.raffle.raffle_id)
+            .is_none_or(|(tickets, _)| *tickets && x.raffle.role == HypernetRaffleRole::Participant)
+    });
+    let needs_assets: Vec<&EvEHypernetRaffle> = raffles_created
+        .iter()
+        .map(|(_, x)| x)
+        .filter(|x| {
+            missing
+                .get(&x.raffle_id)
+                .is_none_or(|(_, baseline)| *baseline)
+        })
+        .collect();
+
+    let journal = if !needs_journal {
+        vec![]
+    } else {
+        get_wallet_journal(&esi, char.character_id)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Error fetching wallet journal for character {}: {:?}",
+                    char.character_id, e
+                );
+                vec![]
+            })
+    };
 
-            let buy_price = buy_orders
+    // Every raffle in the notifications, so a purchase is only counted when it fits one of them.
+    let mut candidates: Vec<ReconcileRaffle> = raffles_created
+        .iter()
+        .map(|(_, x)| ReconcileRaffle::from(x))
+        .collect();
+    for (notification, status) in raffles_expired
+        .iter()
+        .map(|x| (x, HypernetRaffleStatus::Expired))
+        .chain(
+            raffles_finished
                 .iter()
-                .filter(|x| x.location_id == 60003760) // Jita 4-4
-                .map(|x| x.price)
-                .max_by(|a, b| a.partial_cmp(b).unwrap());
+                .map(|x| (x, HypernetRaffleStatus::Finished)),
+        )
+    {
+        let ended = &notification.raffle;
+        match candidates
+            .iter_mut()
+            .find(|x| x.raffle_id == ended.raffle_id)
+        {
+            Some(candidate) => {
+                candidate.status = status;
+                candidate.ended_at = Some(ended.created_at);
+            }
+            None => candidates.push(ReconcileRaffle {
+                status,
+                ended_at: Some(ended.created_at),
+                ..ReconcileRaffle::from(ended)
+            }),
+        }
+    }
 
-            price_cache.insert(raffle.type_id, (sell_price, buy_price));
-            prices = price_cache.get(&raffle.type_id);
+    // Insert new raffles
+    let mut transaction = ctx.postgres.begin().await?;
+    for (notification_id, mut raffle) in raffles_created.iter().cloned() {
+        let purchase = derive_ticket_purchase(&raffle, &candidates, &journal);
+        raffle.owned_tickets = purchase.as_ref().map(|x| x.tickets);
+        raffle.isk_spent = purchase.as_ref().map(|x| x.isk_spent);
+        let inserted = insert_raffle(&mut transaction, &esi, prices, &raffle).await?;
+
+        // New raffles are announced too, if the character enabled it.
+        if inserted {
+            let query = query_file!(
+                "./sql/processed_notifications/insert_pending.sql",
+                notification_id,
+                char.character_id,
+                raffle.raffle_id,
+                RAFFLE_CREATED,
+                HypernetRaffleStatus::Created as HypernetRaffleStatus,
+            );
+            transaction.execute(query).await?;
         }
-        let prices = prices.unwrap_or(&(None, None));
 
-        let query = query_file!(
-            "./sql/hypernet_raffle/insert_raffle.sql",
-            raffle.location_id,
-            raffle.owner_id,
-            raffle.character_id,
-            raffle.raffle_id,
-            raffle.ticket_count,
-            raffle.ticket_price,
-            raffle.type_id,
-            raffle.status as HypernetRaffleStatus,
-            raffle.result as HypernetRaffleResult,
-            raffle.created_at,
-            prices.0,
-            prices.1,
-            hypernet_core_buy_price.clone(),
-            hypernet_core_sell_price.clone(),
-            plex_price.clone()
-        );
-        transaction.execute(query).await?;
+        // Tickets may have been bought after the raffle was first inserted.
+        if let Some(purchase) = purchase {
+            let query = query_file!(
+                "./sql/hypernet_raffle/update_owned_tickets_from_wallet.sql",
+                raffle.raffle_id,
+                raffle.character_id,
+                purchase.tickets,
+                purchase.isk_spent
+            );
+            transaction.execute(query).await?;
+        }
     }
     transaction.commit().await?;
 
-    // Figure out which raffles have expired and which have finished
-    let created_raffle_ids: HashSet<String> = raffles_created
-        .iter()
-        .map(|r| r.raffle_id.clone())
-        .collect();
-    let expired_raffle_ids: HashSet<String> = raffles_expired
-        .iter()
-        .map(|r| r.raffle_id.clone())
-        .collect();
-    let finished_raffle_ids: HashSet<String> = raffles_finished
-        .iter()
-        .map(|r| r.raffle_id.clone())
-        .collect();
+    // Remember how many of the item the character still holds at the raffle location, so a
+    // returning item can be recognized as a win later on.
+    if !needs_assets.is_empty() {
+        match esi
+            .group_assets()
+            .get_character_assets(char.character_id)
+            .await
+        {
+            Ok(assets) => {
+                for raffle in needs_assets {
+                    query_file!(
+                        "./sql/hypernet_raffle/update_asset_baseline.sql",
+                        raffle.raffle_id,
+                        raffle.character_id,
+                        held_quantity(&assets, raffle.type_id, raffle.location_id)
+                    )
+                    .execute(&ctx.postgres)
+                    .await?;
+                }
+            }
+            Err(e) => warn!(
+                "Error fetching assets for character {}: {:?}",
+                char.character_id, e
+            ),
+        }
+    }
 
-    // Raffle Ids that are both created and expired/finished
-    let newly_expired_raffle_ids: HashSet<String> = created_raffle_ids
-        .intersection(&expired_raffle_ids)
-        .cloned()
-        .collect();
-    let newly_finished_raffle_ids: HashSet<String> = created_raffle_ids
-        .intersection(&finished_raffle_ids)
-        .cloned()
+    // Apply expiries and finishes in the order they happened. The stored raffle decides
+    // whether a notification still needs to be handled, so it does not matter whether the
+    // RaffleCreated notification is still part of the ESI response.
+    let mut transitions: Vec<(RaffleNotification, HypernetRaffleStatus)> = raffles_expired
+        .into_iter()
+        .map(|r| (r, HypernetRaffleStatus::Expired))
+        .chain(
+            raffles_finished
+                .into_iter()
+                .map(|r| (r, HypernetRaffleStatus::Finished)),
+        )
         .collect();
+    transitions.sort_by_key(|(r, _)| r.raffle.created_at);
 
-    //TODO: We should fetch the DB raffles to ensure its not already finished.
-
-    // Raffles to check
-    let raffles_to_check = newly_expired_raffle_ids
-        .union(&newly_finished_raffle_ids)
-        .cloned()
-        .collect::<Vec<_>>();
+    for (notification, next_status) in transitions {
+        let already_processed: bool = query_file_scalar!(
+            "./sql/processed_notifications/exists.sql",
+            notification.notification_id
+        )
+        .fetch_one(&ctx.postgres)
+        .await?;
+        if already_processed {
+            continue;
+        }
 
-    for raffle_id in raffles_to_check {
-        let raffle: EvEHypernetRaffle = query_file_as!(
+        let raffle_id = notification.raffle.raffle_id.clone();
+        let stored_raffle: Option<EvEHypernetRaffle> = query_file_as!(
             EvEHypernetRaffle,
             "./sql/hypernet_raffle/select_raffle_by_id.sql",
-            raffle_id
+            raffle_id,
+            char.character_id
         )
-        .fetch_one(&ctx.postgres)
+        .fetch_optional(&ctx.postgres)
         .await?;
 
-        debug!("Checking raffle {}", raffle_id);
-
-        if let HypernetRaffleStatus::Created = raffle.status {
-            let (embed, status) = if newly_expired_raffle_ids.contains(&raffle_id) {
-                // Send Expired Discord Notification.
-                (
-                    build_embed(&raffle, &esi, HypernetRaffleStatus::Expired).await?,
-                    HypernetRaffleStatus::Expired,
-                )
-            } else if newly_finished_raffle_ids.contains(&raffle_id) {
-                (
-                    build_embed(&raffle, &esi, HypernetRaffleStatus::Finished).await?,
-                    HypernetRaffleStatus::Finished,
+        let raffle = match stored_raffle {
+            Some(raffle) => raffle,
+            None => {
+                debug!("Creating placeholder for unknown raffle {}", raffle_id);
+                let mut placeholder = EvEHypernetRaffle {
+                    placeholder: true,
+                    ..notification.raffle.clone()
+                };
+                if let Some(purchase) = derive_ticket_purchase(&placeholder, &candidates, &journal)
+                {
+                    placeholder.owned_tickets = Some(purchase.tickets);
+                    placeholder.isk_spent = Some(purchase.isk_spent);
+                }
+                let mut transaction = ctx.postgres.begin().await?;
+                insert_raffle(&mut transaction, &esi, prices, &placeholder).await?;
+                transaction.commit().await?;
+
+                query_file_as!(
+                    EvEHypernetRaffle,
+                    "./sql/hypernet_raffle/select_raffle_by_id.sql",
+                    raffle_id,
+                    char.character_id
                 )
-            } else {
-                unreachable!();
-            };
+                .fetch_one(&ctx.postgres)
+                .await?
+            }
+        };
 
-            let channel_id = ChannelId::new(
-                notification_channel_id.ok_or(anyhow!("No notification channel set for user"))?
-                    as u64,
+        if raffle.status == next_status {
+            // Already handled through another notification.
+            continue;
+        }
+        if !raffle.status.can_transition_to(next_status) {
+            warn!(
+                "Ignoring illegal transition of raffle {} from {} to {}",
+                raffle_id, raffle.status, next_status
             );
-            let mut message = CreateMessage::new().embed(embed);
-
-            if let HypernetRaffleStatus::Finished = status {
-                let won_button = CreateButton::new(format!("raffle-won:{}", raffle.raffle_id))
-                    .style(ButtonStyle::Success)
-                    .label("Won Raffle");
-                let lost_button = CreateButton::new(format!("raffle-lost:{}", raffle.raffle_id))
-                    .style(ButtonStyle::Danger)
-                    .label("Lost Raffle");
-                let action_row = CreateActionRow::Buttons(vec![won_button, lost_button]);
-                message = message.components(vec![action_row]);
-            }
+            continue;
+        }
+
+        debug!("Raffle {} changed status to {}", raffle_id, next_status);
 
-            channel_id.send_message(&ctx.discord_http, message).await?;
+        // The transition and the pending ledger entry are stored together, the message is only
+        // sent afterwards. A restart in between leaves a pending entry that is delivered below.
+        let mut transaction = ctx.postgres.begin().await?;
+        let transitioned = query_file!(
+            "./sql/hypernet_raffle/transition_status.sql",
+            raffle_id,
+            char.character_id,
+            raffle.status as HypernetRaffleStatus,
+            next_status as HypernetRaffleStatus,
+        )
+        .execute(&mut *transaction)
+        .await?;
+        if transitioned.rows_affected() == 0 {
+            continue;
         }
+        query_file!(
+            "./sql/raffle_events/insert_event.sql",
+            raffle_id,
+            char.character_id,
+            RaffleEventKind::StatusChanged as RaffleEventKind,
+            Some(next_status) as Option<HypernetRaffleStatus>,
+            None as Option<HypernetRaffleResult>,
+            Some(notification.notification_id),
+            None as Option<i64>,
+            None as Option<String>,
+            notification.raffle.created_at,
+        )
+        .execute(&mut *transaction)
+        .await?;
+        query_file!(
+            "./sql/processed_notifications/insert_pending.sql",
+            notification.notification_id,
+            char.character_id,
+            raffle_id,
+            notification.notification_type,
+            next_status as HypernetRaffleStatus,
+        )
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
     }
 
-    // Update raffle statuses
-    let mut transaction = ctx.postgres.begin().await?;
-    for raffle in raffles_expired {
-        let query = query_file!(
-            "./sql/hypernet_raffle/update_status.sql",
-            raffle.raffle_id,
-            HypernetRaffleStatus::Expired as HypernetRaffleStatus,
-        );
-        transaction.execute(query).await?;
+    // Queue everything that is stored but not yet delivered, including leftovers of earlier runs.
+    let pending: Vec<ProcessedNotification> = query_file_as!(
+        ProcessedNotification,
+        "./sql/processed_notifications/select_pending_for_character.sql",
+        char.character_id
+    )
+    .fetch_all(&ctx.postgres)
+    .await?;
+
+    for notification in pending {
+        let raffle: EvEHypernetRaffle = query_file_as!(
+            EvEHypernetRaffle,
+            "./sql/hypernet_raffle/select_raffle_by_id.sql",
+            notification.raffle_id,
+            notification.character_id
+        )
+        .fetch_one(&ctx.postgres)
+        .await?;
+
+        let embed =
+            build_embed(&ctx.postgres, &esi, estimator, &raffle, notification.status).await?;
+        let mut message = CreateMessage::new().embed(embed);
+
+        if let HypernetRaffleStatus::Finished = notification.status {
+            let won_button = CreateButton::new(format!("raffle-won:{}", raffle.key()))
+                .style(ButtonStyle::Success)
+                .label("Won Raffle");
+            let lost_button = CreateButton::new(format!("raffle-lost:{}", raffle.key()))
+                .style(ButtonStyle::Danger)
+                .label("Lost Raffle");
+            let action_row = CreateActionRow::Buttons(vec![won_button, lost_button]);
+            message = message.components(vec![action_row]);
+        }
+
+        // Handing the message to the outbox counts as delivered, the sender task retries it.
+        let mut transaction = ctx.postgres.begin().await?;
+        let kind = match notification.status {
+            HypernetRaffleStatus::Created => NotificationEventKind::Created,
+            HypernetRaffleStatus::Expired => NotificationEventKind::Expired,
+            HypernetRaffleStatus::Finished => NotificationEventKind::Finished,
+        };
+        let event = NotificationEvent::new(kind, &char, Some(&raffle), &message)?;
+        enqueue_event(&mut transaction, &event).await?;
+        query_file!(
+            "./sql/processed_notifications/mark_delivered.sql",
+            notification.notification_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+    }
+
+    Ok(())
+}
+
+async fn insert_raffle(
+    transaction: &mut Transaction<'_, Postgres>,
+    esi: &Esi,
+    prices: &mut MarketPrices,
+    raffle: &EvEHypernetRaffle,
+) -> anyhow::Result<bool> {
+    let (sell_price, buy_price) = prices.item_prices(esi, raffle.type_id).await?;
+
+    let query = query_file!(
+        "./sql/hypernet_raffle/insert_raffle.sql",
+        raffle.location_id,
+        raffle.owner_id,
+        raffle.character_id,
+        raffle.raffle_id,
+        raffle.ticket_count,
+        raffle.ticket_price,
+        raffle.type_id,
+        raffle.status as HypernetRaffleStatus,
+        raffle.result as HypernetRaffleResult,
+        raffle.created_at,
+        sell_price,
+        buy_price,
+        prices.hypercore_buy_price,
+        prices.hypercore_sell_price,
+        prices.plex_price,
+        raffle.owned_tickets,
+        raffle.placeholder,
+        raffle.role as HypernetRaffleRole,
+        raffle.isk_spent
+    );
+    let inserted = transaction.execute(query).await?;
+    if inserted.rows_affected() == 0 {
+        return Ok(false);
     }
 
-    for raffle in raffles_finished {
+    let query = query_file!(
+        "./sql/raffle_events/insert_event.sql",
+        raffle.raffle_id,
+        raffle.character_id,
+        RaffleEventKind::Created as RaffleEventKind,
+        Some(raffle.status) as Option<HypernetRaffleStatus>,
+        None as Option<HypernetRaffleResult>,
+        None as Option<i64>,
+        None as Option<i64>,
+        raffle
+            .placeholder
+            .then(|| json!({ "placeholder": true }).to_string()),
+        raffle.created_at,
+    );
+    transaction.execute(query).await?;
+
+    // Only a raffle we saw being created tells us when its Hypercores were burned.
+    let cores = RaffleSetup {
+        plex_price: prices.plex_price,
+        ..RaffleSetup::from(raffle)
+    }
+    .required_cores();
+    if let (HypernetRaffleRole::Owner, false, Some(cores)) =
+        (raffle.role, raffle.placeholder, cores)
+    {
         let query = query_file!(
-            "./sql/hypernet_raffle/update_status.sql",
+            "./sql/hypercore/insert_consumption.sql",
             raffle.raffle_id,
-            HypernetRaffleStatus::Finished as HypernetRaffleStatus,
+            raffle.character_id,
+            cores as i32,
+            raffle.created_at
         );
         transaction.execute(query).await?;
     }
-    transaction.commit().await?;
 
-    Ok(())
+    let query = query_file!(
+        "./sql/raffle_events/insert_event.sql",
+        raffle.raffle_id,
+        raffle.character_id,
+        RaffleEventKind::PriceSnapshot as RaffleEventKind,
+        None as Option<HypernetRaffleStatus>,
+        None as Option<HypernetRaffleResult>,
+        None as Option<i64>,
+        None as Option<i64>,
+        Some(
+            json!({
+                "sell_price": sell_price,
+                "buy_price": buy_price,
+                "hypercore_buy_price": prices.hypercore_buy_price,
+                "hypercore_sell_price": prices.hypercore_sell_price,
+                "plex_price": prices.plex_price,
+            })
+            .to_string()
+        ),
+        Utc::now(),
+    );
+    transaction.execute(query).await?;
+
+    Ok(true)
 }
 
 async fn build_embed(
-    raffle: &EvEHypernetRaffle,
+    postgres: &PgPool,
     esi: &Esi,
+    estimator: &FillEstimator,
+    raffle: &EvEHypernetRaffle,
     current_status: HypernetRaffleStatus,
 ) -> Result<CreateEmbed, anyhow::Error> {
-    let item = esi.group_universe().get_type(raffle.type_id).await?;
+    let item_name = type_name(postgres, esi, raffle.type_id).await?;
     let color = match current_status {
         HypernetRaffleStatus::Expired => Colour::from((255, 0, 0)),
         HypernetRaffleStatus::Finished => Colour::from((0, 255, 0)),
         _ => Colour::from((255, 255, 255)),
     };
-    let profit_win = calculate_profit(raffle, Winner);
-    let profit_lose = calculate_profit(raffle, Loser);
+    let setup = RaffleSetup::from(raffle);
 
-    let expected_value = (|| Some((profit_lose? + profit_win?) * 0.5))();
+    let description = match raffle.role {
+        HypernetRaffleRole::Owner if raffle.placeholder => format!(
+            "Hypernet Raffle changed status to {}\n\
+            We did not see this raffle being created, so prices are from when it ended.",
+            current_status
+        ),
+        HypernetRaffleRole::Owner if current_status == HypernetRaffleStatus::Created => {
+            "You created a Hypernet Raffle".to_string()
+        }
+        HypernetRaffleRole::Owner => {
+            format!("Hypernet Raffle changed status to {}", current_status)
+        }
+        HypernetRaffleRole::Participant => format!(
+            "A Hypernet Raffle you bought tickets in changed status to {}",
+            current_status
+        ),
+    };
 
-    Ok(CreateEmbed::new()
+    let embed = CreateEmbed::new()
         .title(format!("Hypernet Raffle {}", current_status))
-        .description(format!(
-            "Hypernet Raffle changed status to {}",
-            current_status
-        ))
+        .description(description)
         .thumbnail(format!(
             "https://images.evetech.net/types/{}/icon",
             raffle.type_id
         ))
         .color(color)
-        .field("Item", item.name, true)
+        .field("Item", item_name, true)
         .field(
             "Marked Value (Sell)",
             raffle
@@ -371,15 +661,85 @@ async fn build_embed(
             "Ticket Price",
             raffle.ticket_price.separate_with_dots(),
             true,
+        );
+
+    let mut embed = match raffle.role {
+        HypernetRaffleRole::Owner => owner_fields(embed, raffle, &setup, estimator),
+        HypernetRaffleRole::Participant => participant_fields(embed, raffle, &setup),
+    };
+
+    let realized = query_file!(
+        "./sql/raffle_wallet_entries/select_realized_for_raffle.sql",
+        raffle.raffle_id,
+        raffle.character_id
+    )
+    .fetch_one(postgres)
+    .await?;
+    if realized.entries > 0 {
+        embed = embed
+            .field(
+                "Realized ISK (In / Out)",
+                format!(
+                    "{} / {}",
+                    realized.isk_in.round().separate_with_dots(),
+                    realized.isk_out.round().separate_with_dots()
+                ),
+                true,
+            )
+            .field(
+                "Profit (Realized / Estimated)",
+                format!(
+                    "{} / {}",
+                    realized_profit(
+                        raffle,
+                        realized.isk_in,
+                        realized.isk_out,
+                        realized.settlements > 0
+                    )
+                    .map(|x| x.round().separate_with_dots())
+                    .unwrap_or("Pending".to_string()),
+                    estimated_profit(raffle, ProfitView::MarkToMarket)
+                        .map(|x| x.round().separate_with_dots())
+                        .unwrap_or("Pending".to_string())
+                ),
+                true,
+            );
+    }
+
+    Ok(embed.footer(CreateEmbedFooter::new(format!(
+        "RaffleID: {}",
+        raffle.raffle_id
+    ))))
+}
+
+fn owner_fields(
+    embed: CreateEmbed,
+    raffle: &EvEHypernetRaffle,
+    setup: &RaffleSetup,
+    estimator: &FillEstimator,
+) -> CreateEmbed {
+    let profit_win = calculate_profit(setup, Winner);
+    let profit_lose = calculate_profit(setup, Loser);
+    let expected_value = expected_value(setup);
+    let fill_probability = raffle
+        .sell_price
+        .map(|x| estimator.fill_probability(raffle.type_id, setup, x));
+
+    embed
+        .field(
+            "Your Tickets",
+            match raffle.owned_tickets {
+                Some(x) => x.separate_with_dots(),
+                None => format!("{} (assumed)", setup.owned_tickets().separate_with_dots()),
+            },
+            true,
         )
         .field(
-            "Payout",
-            // 95% of the total ticket price. 5% because of tax.
-            (raffle.ticket_count as f64 * raffle.ticket_price * 0.95_f64)
-                .round()
-                .separate_with_dots(),
+            "Win Chance",
+            format!("{:.1}%", win_probability(setup) * 100.0),
             true,
         )
+        .field("Payout", setup.payout().round().separate_with_dots(), true)
         .field(
             "Profit (Win)",
             profit_win
@@ -401,140 +761,72 @@ async fn build_embed(
                 .unwrap_or("Unknown".to_string()),
             true,
         )
-        .footer(CreateEmbedFooter::new(format!(
-            "RaffleID: {}",
-            raffle.raffle_id
-        ))))
-}
-
-fn parse_raffles(
-    raffles: &[&Notification],
-    char_id: i32,
-) -> Result<Vec<EvEHypernetRaffle>, anyhow::Error> {
-    let mut eve_raffles: Vec<EvEHypernetRaffle> = vec![];
-    for raffle in raffles.iter() {
-        let text = raffle.text.as_ref().unwrap();
-        let parts: HashMap<String, String> = text
-            .split('\n')
-            .filter(|x| !x.is_empty())
-            .map(|x| x.trim().splitn(2, ": "))
-            .map(|x| x.map(|y| y.to_string()).collect::<Vec<_>>())
-            .map(|x| (x[0].clone(), x[1].clone()))
-            .collect();
-
-        let owner_id = parts
-            .get("owner_id")
-            .ok_or(anyhow!("Missing owner_id"))?
-            .parse::<i32>()?;
-        let raffle_id = parts
-            .get("raffle_id")
-            .ok_or(anyhow!("Missing raffle_id"))?
-            .clone();
-        let location_id = parts
-            .get("location_id")
-            .ok_or(anyhow!("Missing location_id"))?
-            .parse::<i32>()?;
-        let ticket_price = parts
-            .get("ticket_price")
-            .ok_or(anyhow!("Missing ticket_price"))?
-            .parse::<f64>()?;
-        let ticket_count = parts
-            .get("ticket_count")
-            .ok_or(anyhow!("Missing ticket_count"))?
-            .parse::<i32>()?;
-        let type_id = parts
-            .get("type_id")
-            .ok_or(anyhow!("Missing type_id"))?
-            .parse::<i32>()?;
-
-        eve_raffles.push(EvEHypernetRaffle {
-            location_id,
-            owner_id,
-            character_id: char_id,
-            raffle_id,
-            ticket_count,
-            ticket_price,
-            type_id,
-            buy_price: None,
-            sell_price: None,
-            hypercore_sell_price: None,
-            hypercore_buy_price: None,
-            plex_price: None,
-            status: HypernetRaffleStatus::Created,
-            result: HypernetRaffleResult::None,
-            created_at: chrono::DateTime::from_str(&raffle.timestamp)?,
-        });
-    }
-
-    Ok(eve_raffles)
-}
-
-enum ProfitType {
-    Winner,
-    Loser,
-}
-
-fn calculate_profit(raffle: &EvEHypernetRaffle, status: ProfitType) -> Option<f64> {
-    let item_value = raffle.ticket_count as f64 * raffle.ticket_price;
-    let payout = item_value * 0.95; // 95% of the total ticket price. 5% because of tax.
-    let plex_price = raffle.plex_price?;
-    let required_cores = (item_value / (2.0 * plex_price)).floor();
-
-    // If we win, we get the item back and the payout. But we spend 50% of the item_value on tickets
-    // If we lose, we get nothing back. But we spend 50% of the item_value on tickets
-    let profit = match status {
-        Winner => {
-            let total_income = raffle.buy_price? + payout;
-            let total_expense = raffle.buy_price?
-                + (required_cores * raffle.hypercore_sell_price?)
-                + 0.5 * item_value;
-            total_income - total_expense
-        }
-        Loser => {
-            let total_expense = raffle.buy_price?
-                + (required_cores * raffle.hypercore_sell_price?)
-                + 0.5 * item_value;
-            payout - total_expense
-        }
-    };
-
-    Some(profit)
+        .field(
+            "Predicted Sell-out",
+            fill_probability
+                .map(|x| format!("{:.1}%", x * 100.0))
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
+        .field(
+            "Expected Value (incl. Expiry)",
+            fill_probability
+                .and_then(|x| fill_weighted_expected_value(setup, x))
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::cron::collect_hypernet_task::calculate_profit;
-    use crate::cron::collect_hypernet_task::ProfitType::{Loser, Winner};
-    use crate::database::hypernet_raffle_model::{
-        EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleStatus,
-    };
-
-    #[tokio::test]
-    async fn calculate_profit_test() {
-        let raffle = EvEHypernetRaffle {
-            owner_id: 1,
-            character_id: 1,
-            location_id: 1,
-            raffle_id: "1".to_string(),
-            ticket_count: 8,
-            ticket_price: 10307323.0,
-            type_id: 1,
-            buy_price: Some(54730000.0),
-            sell_price: Some(58430000.0),
-            hypercore_buy_price: Some(304000.0),
-            hypercore_sell_price: Some(327600.0),
-            plex_price: Some(5736785.34),
-            status: HypernetRaffleStatus::Created,
-            result: HypernetRaffleResult::None,
-            created_at: chrono::Utc::now(),
-        };
-
-        let profit = calculate_profit(&raffle, Winner).unwrap();
-        dbg!(profit);
-        assert!(profit > 0.0);
-
-        let profit = calculate_profit(&raffle, Loser).unwrap();
-        dbg!(profit);
-        assert!(profit < 0.0);
-    }
+fn participant_fields(
+    embed: CreateEmbed,
+    raffle: &EvEHypernetRaffle,
+    setup: &RaffleSetup,
+) -> CreateEmbed {
+    embed
+        .field(
+            "Your Tickets",
+            raffle
+                .owned_tickets
+                .map(|x| x.separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
+        .field(
+            "ISK Spent",
+            raffle
+                .isk_spent
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
+        .field(
+            "Win Chance",
+            raffle
+                .owned_tickets
+                .map(|_| format!("{:.1}%", win_probability(setup) * 100.0))
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
+        .field(
+            "Profit (Win)",
+            participant_profit(setup, Winner)
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
+        .field(
+            "Profit (Lose)",
+            participant_profit(setup, Loser)
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
+        .field(
+            "Expected Value",
+            participant_expected_value(setup)
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        )
 }
@@ -0,0 +1,148 @@
+use crate::context::CronAppContext;
+use crate::cron::CronTask;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::database::wallet_transaction::StoredWalletTransaction;
+use crate::esi::wallet_transactions::get_wallet_transactions;
+use crate::hypernet::cost_basis::raffle_cost_basis;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::warn;
+use rfesi::prelude::Esi;
+use sqlx::{query_file, query_file_as, PgPool};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Stores the characters' market transactions and matches them FIFO against the raffles they
+/// created, so profit can be shown at what the items and Hypercores actually cost.
+pub struct CostBasisTask;
+
+#[async_trait]
+impl CronTask for CostBasisTask {
+    fn name(&self) -> &'static str {
+        "CostBasisTask"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(3600)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(120)
+    }
+
+    async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
+        let all_chars: Vec<EvECharacterInfo> = query_file_as!(
+            EvECharacterInfo,
+            "./sql/eve_character/select_all_characters.sql"
+        )
+        .fetch_all(&ctx.postgres)
+        .await?;
+
+        for char in all_chars {
+            if let Err(e) = update_character(&ctx, &char).await {
+                warn!(
+                    "Error updating cost basis of character {}: {:?}",
+                    char.character_id, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Stores the character's recent market transactions next to the ones we already know.
+pub async fn sync_wallet_transactions(
+    postgres: &PgPool,
+    esi: &Esi,
+    character_id: i32,
+) -> anyhow::Result<()> {
+    let transactions = get_wallet_transactions(esi, character_id).await?;
+
+    let mut transaction = postgres.begin().await?;
+    for wallet_transaction in transactions {
+        let Some(date) = wallet_transaction.date() else {
+            continue;
+        };
+        query_file!(
+            "./sql/wallet_transactions/insert_transaction.sql",
+            wallet_transaction.transaction_id,
+            character_id,
+            wallet_transaction.type_id,
+            wallet_transaction.quantity,
+            wallet_transaction.unit_price,
+            wallet_transaction.is_buy,
+            date
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// When the character's raffles expired or finished, by raffle.
+pub async fn select_end_times(
+    postgres: &PgPool,
+    character_id: i32,
+) -> anyhow::Result<HashMap<String, DateTime<Utc>>> {
+    Ok(query_file!(
+        "./sql/raffle_events/select_end_times_for_character.sql",
+        character_id
+    )
+    .fetch_all(postgres)
+    .await?
+    .into_iter()
+    .map(|x| (x.raffle_id, x.ended_at))
+    .collect())
+}
+
+async fn update_character(ctx: &CronAppContext, char: &EvECharacterInfo) -> anyhow::Result<()> {
+    let mut esi = ctx.esi.clone();
+    esi.use_refresh_token(&char.refresh_token).await?;
+    sync_wallet_transactions(&ctx.postgres, &esi, char.character_id).await?;
+
+    let raffles: Vec<EvEHypernetRaffle> = query_file_as!(
+        EvEHypernetRaffle,
+        "./sql/hypernet_raffle/select_owned_raffles_for_character.sql",
+        char.character_id
+    )
+    .fetch_all(&ctx.postgres)
+    .await?;
+    if raffles.is_empty() {
+        return Ok(());
+    }
+
+    // Stored transactions reach further back than ESI does.
+    let stored: Vec<StoredWalletTransaction> = query_file_as!(
+        StoredWalletTransaction,
+        "./sql/wallet_transactions/select_transactions_for_character.sql",
+        char.character_id
+    )
+    .fetch_all(&ctx.postgres)
+    .await?;
+
+    let ended_at = select_end_times(&ctx.postgres, char.character_id).await?;
+
+    let mut transaction = ctx.postgres.begin().await?;
+    for (raffle_id, (item_cost_basis, hypercore_unit_cost)) in
+        raffle_cost_basis(&raffles, &ended_at, &stored)
+    {
+        query_file!(
+            "./sql/hypernet_raffle/update_cost_basis.sql",
+            raffle_id,
+            char.character_id,
+            item_cost_basis,
+            hypercore_unit_cost
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+    transaction.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use crate::context::CronAppContext;
+use crate::cron::cost_basis_task::{select_end_times, sync_wallet_transactions};
+use crate::cron::CronTask;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::hypercore_inventory::HypercoreInventory;
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::database::notification_preference::NotificationEventKind;
+use crate::database::wallet_transaction::StoredWalletTransaction;
+use crate::hypernet::cost_basis::hypercore_match;
+use crate::hypernet::prices::HYPERCORE_TYPE_ID;
+use crate::notify::{enqueue_event, NotificationEvent};
+use async_trait::async_trait;
+use chrono::Utc;
+use log::warn;
+use serenity::all::{Colour, CreateEmbed, CreateMessage};
+use sqlx::{query_file, query_file_as, query_file_scalar};
+use std::time::Duration;
+use thousands::Separable;
+
+/// Listing history the typical core usage is taken from.
+const USAGE_WINDOW_DAYS: i64 = 14;
+/// Alert when the held cores last fewer days than this at the typical usage.
+const LOW_CORE_DAYS: f64 = 3.0;
+
+/// Keeps track of the Hypercores each character holds and what they paid for them, and warns
+/// before a character runs out.
+pub struct HypercoreTask;
+
+#[async_trait]
+impl CronTask for HypercoreTask {
+    fn name(&self) -> &'static str {
+        "HypercoreTask"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(3600)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(120)
+    }
+
+    async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
+        let all_chars: Vec<EvECharacterInfo> = query_file_as!(
+            EvECharacterInfo,
+            "./sql/eve_character/select_all_characters.sql"
+        )
+        .fetch_all(&ctx.postgres)
+        .await?;
+
+        for char in all_chars {
+            if let Err(e) = update_character(&ctx, &char).await {
+                warn!(
+                    "Error updating Hypercores of character {}: {:?}",
+                    char.character_id, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn update_character(ctx: &CronAppContext, char: &EvECharacterInfo) -> anyhow::Result<()> {
+    let mut esi = ctx.esi.clone();
+    esi.use_refresh_token(&char.refresh_token).await?;
+
+    let assets = esi
+        .group_assets()
+        .get_character_assets(char.character_id)
+        .await?;
+    let quantity: i32 = assets
+        .iter()
+        .filter(|x| x.type_id == HYPERCORE_TYPE_ID)
+        .map(|x| x.quantity)
+        .sum();
+
+    sync_wallet_transactions(&ctx.postgres, &esi, char.character_id).await?;
+    let transactions: Vec<StoredWalletTransaction> = query_file_as!(
+        StoredWalletTransaction,
+        "./sql/wallet_transactions/select_transactions_for_character.sql",
+        char.character_id
+    )
+    .fetch_all(&ctx.postgres)
+    .await?;
+    let raffles: Vec<EvEHypernetRaffle> = query_file_as!(
+        EvEHypernetRaffle,
+        "./sql/hypernet_raffle/select_owned_raffles_for_character.sql",
+        char.character_id
+    )
+    .fetch_all(&ctx.postgres)
+    .await?;
+    let ended_at = select_end_times(&ctx.postgres, char.character_id).await?;
+    let average_cost = hypercore_match(&raffles, &ended_at, &transactions).average_remaining_cost();
+
+    let consumed: i64 = query_file_scalar!(
+        "./sql/hypercore/select_consumed_since.sql",
+        char.character_id,
+        Utc::now() - chrono::Duration::days(USAGE_WINDOW_DAYS)
+    )
+    .fetch_one(&ctx.postgres)
+    .await?;
+    let daily_usage = consumed as f64 / USAGE_WINDOW_DAYS as f64;
+
+    let previous: Option<HypercoreInventory> = query_file_as!(
+        HypercoreInventory,
+        "./sql/hypercore/select_inventory.sql",
+        char.character_id
+    )
+    .fetch_optional(&ctx.postgres)
+    .await?;
+    let alert_sent = previous.is_some_and(|x| x.low_alert_sent);
+
+    let low = daily_usage > 0.0 && (quantity as f64) < daily_usage * LOW_CORE_DAYS;
+    if low && !alert_sent {
+        send_low_core_alert(ctx, char, quantity, average_cost, daily_usage).await?;
+    }
+
+    query_file!(
+        "./sql/hypercore/upsert_inventory.sql",
+        char.character_id,
+        quantity,
+        average_cost,
+        daily_usage,
+        low
+    )
+    .execute(&ctx.postgres)
+    .await?;
+
+    Ok(())
+}
+
+async fn send_low_core_alert(
+    ctx: &CronAppContext,
+    char: &EvECharacterInfo,
+    quantity: i32,
+    average_cost: Option<f64>,
+    daily_usage: f64,
+) -> anyhow::Result<()> {
+    let embed = CreateEmbed::new()
+        .title("Low on Hypercores")
+        .description(format!(
+            "{} has {} Hypercores left, enough for about {:.1} days of raffles.",
+            char.character_name,
+            quantity.separate_with_dots(),
+            quantity as f64 / daily_usage
+        ))
+        .thumbnail(format!(
+            "https://images.evetech.net/types/{}/icon",
+            HYPERCORE_TYPE_ID
+        ))
+        .color(Colour::from((230, 126, 34)))
+        .field("Usage per Day", format!("{:.1}", daily_usage), true)
+        .field(
+            "Average Cost",
+            average_cost
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        );
+
+    let event = NotificationEvent::new(
+        NotificationEventKind::LowHypercores,
+        char,
+        None,
+        &CreateMessage::new().embed(embed),
+    )?;
+    enqueue_event(&mut *ctx.postgres.acquire().await?, &event).await?;
+    Ok(())
+}
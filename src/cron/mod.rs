@@ -1,13 +1,42 @@
 mod collect_hypernet_task;
+mod cost_basis_task;
+mod hypercore_task;
+mod opportunity_scan_task;
+mod outbox_sender_task;
+mod reconcile_wallet_task;
+mod resolve_raffle_results_task;
+mod result_reminder_task;
+mod seed_types_task;
+mod train_fill_model_task;
 
 use crate::context::CronAppContext;
 use crate::cron::collect_hypernet_task::CollectHypernetTask;
+use crate::cron::cost_basis_task::CostBasisTask;
+use crate::cron::hypercore_task::HypercoreTask;
+use crate::cron::opportunity_scan_task::OpportunityScanTask;
+use crate::cron::outbox_sender_task::OutboxSenderTask;
+use crate::cron::reconcile_wallet_task::ReconcileWalletTask;
+use crate::cron::resolve_raffle_results_task::ResolveRaffleResultsTask;
+use crate::cron::result_reminder_task::ResultReminderTask;
+use crate::cron::seed_types_task::SeedTypesTask;
+use crate::cron::train_fill_model_task::TrainFillModelTask;
 use async_trait::async_trait;
 use tokio::task::JoinSet;
 use tokio::{select, time};
 
 pub async fn start_cron(ctx: CronAppContext) -> anyhow::Result<()> {
-    let tasks: Vec<Box<dyn CronTask>> = vec![Box::new(CollectHypernetTask)];
+    let tasks: Vec<Box<dyn CronTask>> = vec![
+        Box::new(CollectHypernetTask),
+        Box::new(OpportunityScanTask),
+        Box::new(ResolveRaffleResultsTask),
+        Box::new(ResultReminderTask),
+        Box::new(ReconcileWalletTask),
+        Box::new(CostBasisTask),
+        Box::new(HypercoreTask),
+        Box::new(TrainFillModelTask),
+        Box::new(SeedTypesTask),
+        Box::new(OutboxSenderTask),
+    ];
 
     let mut join_set = JoinSet::new();
 
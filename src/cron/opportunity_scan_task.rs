@@ -0,0 +1,175 @@
+use crate::context::CronAppContext;
+use crate::cron::CronTask;
+use crate::database::opportunity_watchlist::OpportunityWatchlistEntry;
+use crate::esi::market::get_market_group;
+use crate::hypernet::fill_model::FillEstimator;
+use crate::hypernet::planner::{plan, PlanCandidate, PlanGoal};
+use crate::hypernet::prices::MarketPrices;
+use crate::hypernet::profit::RaffleSetup;
+use crate::hypernet::types::type_name;
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use serenity::all::{ChannelId, Colour, CreateEmbed, CreateEmbedFooter, CreateMessage};
+use sqlx::query_file_as;
+use std::collections::BTreeSet;
+use std::env;
+use std::time::Duration;
+use thousands::Separable;
+
+/// Maximum number of items listed in a digest.
+const MAX_OPPORTUNITIES: usize = 15;
+
+/// Scans the watchlist for items where listing a raffle has a positive expected value
+/// and posts a ranked digest to `OPPORTUNITY_CHANNEL_ID`.
+pub struct OpportunityScanTask;
+
+struct Opportunity {
+    type_id: i32,
+    buy_price: f64,
+    candidate: PlanCandidate,
+}
+
+#[async_trait]
+impl CronTask for OpportunityScanTask {
+    fn name(&self) -> &'static str {
+        "OpportunityScanTask"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(6 * 60 * 60)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(15 * 60)
+    }
+
+    async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
+        let Ok(channel_id) = env::var("OPPORTUNITY_CHANNEL_ID") else {
+            debug!("No OPPORTUNITY_CHANNEL_ID set, skipping opportunity scan");
+            return Ok(());
+        };
+        let channel_id = ChannelId::new(channel_id.parse()?);
+
+        let entries: Vec<OpportunityWatchlistEntry> = query_file_as!(
+            OpportunityWatchlistEntry,
+            "./sql/opportunity_watchlist/select_all_entries.sql"
+        )
+        .fetch_all(&ctx.postgres)
+        .await?;
+
+        let mut type_ids = BTreeSet::new();
+        for entry in entries {
+            if let Some(type_id) = entry.type_id {
+                type_ids.insert(type_id);
+            }
+            if let Some(market_group_id) = entry.market_group_id {
+                match get_market_group(&ctx.esi, market_group_id).await {
+                    Ok(group) => type_ids.extend(group.types),
+                    Err(e) => warn!("Error fetching market group {}: {:?}", market_group_id, e),
+                }
+            }
+        }
+
+        if type_ids.is_empty() {
+            return Ok(());
+        }
+
+        let mut prices = MarketPrices::fetch(&ctx.esi, ctx.market_hub).await?;
+        let estimator = FillEstimator::load(&ctx.postgres).await?;
+
+        let mut opportunities = vec![];
+        for type_id in type_ids {
+            let sell_price = match prices.item_prices(&ctx.esi, type_id).await {
+                Ok((sell_price, _)) => sell_price,
+                Err(e) => {
+                    warn!("Error fetching prices for type_id {}: {:?}", type_id, e);
+                    continue;
+                }
+            };
+            // We have to buy the item from the sell orders before we can raffle it.
+            let Some(buy_price) = sell_price else {
+                continue;
+            };
+
+            let base = RaffleSetup {
+                ticket_count: 0,
+                ticket_price: 0.0,
+                owned_tickets: None,
+                buy_price: Some(buy_price),
+                hypercore_sell_price: prices.hypercore_sell_price,
+                plex_price: prices.plex_price,
+            };
+            let best = plan(
+                &base,
+                buy_price,
+                None,
+                |setup| estimator.fill_probability(type_id, setup, buy_price),
+                PlanGoal::MaxExpectedValue,
+                1,
+            )
+            .into_iter()
+            .next();
+
+            if let Some(candidate) = best {
+                if candidate.adjusted_expected_value > 0.0 {
+                    opportunities.push(Opportunity {
+                        type_id,
+                        buy_price,
+                        candidate,
+                    });
+                }
+            }
+        }
+
+        if opportunities.is_empty() {
+            info!("No Hypernet opportunities found");
+            return Ok(());
+        }
+
+        opportunities.sort_by(|a, b| {
+            b.candidate
+                .adjusted_expected_value
+                .total_cmp(&a.candidate.adjusted_expected_value)
+        });
+        let total = opportunities.len();
+        opportunities.truncate(MAX_OPPORTUNITIES);
+
+        let mut embed = CreateEmbed::new()
+            .title("Hypernet Opportunities")
+            .description("Items with a positive expected value after Hypercore cost.")
+            .color(Colour::from((0, 255, 0)))
+            .footer(CreateEmbedFooter::new(format!(
+                "Showing {} of {} opportunities",
+                opportunities.len(),
+                total
+            )));
+
+        for (rank, opportunity) in opportunities.iter().enumerate() {
+            let name = type_name(&ctx.postgres, &ctx.esi, opportunity.type_id)
+                .await
+                .unwrap_or(opportunity.type_id.to_string());
+            let candidate = &opportunity.candidate;
+            embed = embed.field(
+                format!("{}. {}", rank + 1, name),
+                format!(
+                    "Buy: {} ISK\nSetup: {} × {} ISK\nEV: {} (sells out: {:.0}%)",
+                    opportunity.buy_price.round().separate_with_dots(),
+                    candidate.setup.ticket_count,
+                    candidate.setup.ticket_price.separate_with_dots(),
+                    candidate
+                        .adjusted_expected_value
+                        .round()
+                        .separate_with_dots(),
+                    candidate.fill_probability * 100.0
+                ),
+                false,
+            );
+        }
+
+        channel_id
+            .send_message(&ctx.discord_http, CreateMessage::new().embed(embed))
+            .await?;
+
+        Ok(())
+    }
+}
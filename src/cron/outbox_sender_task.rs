@@ -0,0 +1,299 @@
+use crate::context::CronAppContext;
+use crate::cron::CronTask;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::notification_preference::{NotificationBackend, NotificationEventKind};
+use crate::database::outbox_message::{OutboxMessage, OutboxState};
+use crate::notify::digest::{digest_entries, digest_message};
+use crate::notify::discord::DiscordChannelNotifier;
+use crate::notify::{notifier, retry_delay, DeliveryId, NotificationEvent, Notifier, NotifyError};
+use async_trait::async_trait;
+use chrono::Utc;
+use log::warn;
+use serenity::all::{CreateMessage, UserId};
+use sqlx::{query_file, query_file_as, query_file_scalar};
+use std::time::Duration;
+
+/// Messages sent per run, the rest waits for the next one.
+const BATCH_SIZE: i64 = 50;
+/// Attempts before a message is given up.
+const MAX_ATTEMPTS: i32 = 12;
+/// The user is told once a message failed this often.
+const ALERT_AFTER_ATTEMPTS: i32 = 5;
+/// How long claimed messages are left to one run. Longer than the task may run, a message still
+/// claimed after that was cut off and is sent again.
+const CLAIM_DURATION: Duration = Duration::from_secs(5 * 60);
+
+/// Delivers the events queued in the notification outbox through their backend. Failed
+/// deliveries are retried with backoff. Messages are claimed before they are sent, so they go out
+/// once unless a run is cut off between sending and marking them sent. The `DeliveryId` they are
+/// sent with lets receivers drop that duplicate.
+pub struct OutboxSenderTask;
+
+#[async_trait]
+impl CronTask for OutboxSenderTask {
+    fn name(&self) -> &'static str {
+        "OutboxSenderTask"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(30)
+    }
+
+    async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
+        let mut due: Vec<OutboxMessage> = query_file_as!(
+            OutboxMessage,
+            "./sql/notification_outbox/claim_due_messages.sql",
+            BATCH_SIZE,
+            CLAIM_DURATION.as_secs_f64()
+        )
+        .fetch_all(&ctx.postgres)
+        .await?;
+        due.sort_by_key(|message| message.id);
+
+        for message in due {
+            if let Err(e) = send_message(&ctx, &message).await {
+                warn!("Error sending outbox message {}: {:?}", message.id, e);
+            }
+        }
+
+        let digest_users: Vec<i64> =
+            query_file_scalar!("./sql/notification_digest/select_due_users.sql")
+                .fetch_all(&ctx.postgres)
+                .await?;
+        for discord_user_id in digest_users {
+            if let Err(e) = send_digest(&ctx, discord_user_id).await {
+                warn!("Error sending digest of user {}: {:?}", discord_user_id, e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn send_message(ctx: &CronAppContext, message: &OutboxMessage) -> anyhow::Result<()> {
+    let character: EvECharacterInfo = query_file_as!(
+        EvECharacterInfo,
+        "./sql/eve_character/select_character_by_id.sql",
+        message.character_id
+    )
+    .fetch_one(&ctx.postgres)
+    .await?;
+
+    let event: NotificationEvent = serde_json::from_str(&message.payload)?;
+    let error = match notifier(message)
+        .notify(ctx, &character, &event, DeliveryId::Outbox(message.id))
+        .await
+    {
+        Ok(()) => {
+            query_file!("./sql/notification_outbox/mark_sent.sql", message.id)
+                .execute(&ctx.postgres)
+                .await?;
+            return Ok(());
+        }
+        Err(error) => error,
+    };
+
+    let attempts = message.attempts + 1;
+    let last_error = error.to_string();
+
+    match error {
+        NotifyError::Redirected(_) => {
+            // The next attempt goes to the new target, no need to wait.
+            query_file!(
+                "./sql/notification_outbox/mark_retry.sql",
+                message.id,
+                attempts,
+                Utc::now(),
+                last_error
+            )
+            .execute(&ctx.postgres)
+            .await?;
+        }
+        NotifyError::Transient(_) if attempts < MAX_ATTEMPTS => {
+            let next_attempt_at = Utc::now() + retry_delay(attempts);
+            query_file!(
+                "./sql/notification_outbox/mark_retry.sql",
+                message.id,
+                attempts,
+                next_attempt_at,
+                last_error
+            )
+            .execute(&ctx.postgres)
+            .await?;
+            if attempts == ALERT_AFTER_ATTEMPTS {
+                alert_failure(ctx, &subject(message), &character, attempts, &last_error).await;
+            }
+        }
+        NotifyError::Permanent(_) | NotifyError::Transient(_) => {
+            query_file!(
+                "./sql/notification_outbox/mark_failed.sql",
+                message.id,
+                attempts,
+                last_error
+            )
+            .execute(&ctx.postgres)
+            .await?;
+            alert_failure(ctx, &subject(message), &character, attempts, &last_error).await;
+        }
+    }
+
+    Ok(())
+}
+
+fn subject(message: &OutboxMessage) -> String {
+    format!(
+        "Outbox message {} ({} via {})",
+        message.id, message.event_kind, message.backend
+    )
+}
+
+/// Bundles the held raffle messages of a user into one digest and posts it where the user gets
+/// notifications. A digest that failed earlier is sent again, together with anything held since.
+async fn send_digest(ctx: &CronAppContext, discord_user_id: i64) -> anyhow::Result<()> {
+    let mut transaction = ctx.postgres.begin().await?;
+    let open: Option<i64> = query_file_scalar!(
+        "./sql/notification_digest/select_open_digest.sql",
+        discord_user_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    let digest_id = match open {
+        Some(digest_id) => digest_id,
+        None => {
+            query_file_scalar!(
+                "./sql/notification_digest/insert_digest.sql",
+                discord_user_id
+            )
+            .fetch_one(&mut *transaction)
+            .await?
+        }
+    };
+    query_file!(
+        "./sql/notification_digest/attach_messages.sql",
+        discord_user_id,
+        digest_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    query_file!(
+        "./sql/notification_digest/claim_digest.sql",
+        digest_id,
+        CLAIM_DURATION.as_secs_f64()
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+
+    let entries = digest_entries(&ctx.postgres, digest_id).await?;
+    let Some(first) = entries.first() else {
+        return Ok(());
+    };
+    let character: EvECharacterInfo = query_file_as!(
+        EvECharacterInfo,
+        "./sql/eve_character/select_character_by_id.sql",
+        first.character_id
+    )
+    .fetch_one(&ctx.postgres)
+    .await?;
+
+    let message = digest_message(&ctx.postgres, &ctx.esi, digest_id, &entries).await?;
+    // Only the message itself matters to the notifier, the digest goes to the user's channel.
+    let event = NotificationEvent::new(first.event_kind, &character, None, &message)?;
+    let error = match (DiscordChannelNotifier { channel_id: None })
+        .notify(ctx, &character, &event, DeliveryId::Digest(digest_id))
+        .await
+    {
+        Ok(()) => {
+            query_file!("./sql/notification_digest/mark_sent.sql", digest_id)
+                .execute(&ctx.postgres)
+                .await?;
+            return Ok(());
+        }
+        Err(error) => error,
+    };
+
+    let attempts = entries.iter().map(|x| x.attempts).max().unwrap_or(0) + 1;
+    let last_error = error.to_string();
+    let subject = format!("Digest {} ({} events)", digest_id, entries.len());
+
+    match error {
+        NotifyError::Redirected(_) => {
+            query_file!(
+                "./sql/notification_digest/mark_retry.sql",
+                digest_id,
+                attempts,
+                Utc::now(),
+                last_error
+            )
+            .execute(&ctx.postgres)
+            .await?;
+        }
+        NotifyError::Transient(_) if attempts < MAX_ATTEMPTS => {
+            query_file!(
+                "./sql/notification_digest/mark_retry.sql",
+                digest_id,
+                attempts,
+                Utc::now() + retry_delay(attempts),
+                last_error
+            )
+            .execute(&ctx.postgres)
+            .await?;
+            if attempts == ALERT_AFTER_ATTEMPTS {
+                alert_failure(ctx, &subject, &character, attempts, &last_error).await;
+            }
+        }
+        NotifyError::Permanent(_) | NotifyError::Transient(_) => {
+            query_file!(
+                "./sql/notification_digest/mark_failed.sql",
+                digest_id,
+                attempts,
+                last_error
+            )
+            .execute(&ctx.postgres)
+            .await?;
+            alert_failure(ctx, &subject, &character, attempts, &last_error).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Tells the user about a message that keeps failing, or the bot owners if the user can't be
+/// reached either. Sent directly, not through the outbox, a broken outbox is exactly what they
+/// should hear about.
+async fn alert_failure(
+    ctx: &CronAppContext,
+    subject: &str,
+    character: &EvECharacterInfo,
+    attempts: i32,
+    last_error: &str,
+) {
+    let user_id = UserId::new(character.discord_user_id as u64);
+    let content = format!(
+        "{} for {} failed {} times, last error: {}\n\
+         Check where your notifications go with /notification_settings and /notification_backends.",
+        subject, character.character_name, attempts, last_error
+    );
+    let Err(e) = send_direct(ctx, user_id, &content).await else {
+        return;
+    };
+    warn!("Error alerting user {}: {:?}", user_id, e);
+
+    let content = format!(
+        "{} for {} (<@{}>) failed {} times, last error: {}\n\
+         The user couldn't be told either: {}",
+        subject, character.character_name, character.discord_user_id, attempts, last_error, e
+    );
+    for owner in &ctx.owners {
+        if let Err(e) = send_direct(ctx, *owner, &content).await {
+            warn!("Error alerting owner {}: {:?}", owner, e);
+        }
+    }
+}
+
+async fn send_direct(ctx: &CronAppContext, user_id: UserId, content: &str) -> anyhow::Result<()> {
+    user_id
+        .direct_message(&ctx.discord_http, CreateMessage::new().content(content))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::context::CronAppContext;
+use crate::cron::CronTask;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::hypernet_raffle_model::{HypernetRaffleRole, HypernetRaffleStatus};
+use crate::database::raffle_wallet_entry::RaffleWalletEntryKind;
+use crate::esi::wallet_journal::get_wallet_journal;
+use crate::hypernet::reconcile::{match_entry, ReconcileRaffle};
+use async_trait::async_trait;
+use chrono::Utc;
+use log::{debug, warn};
+use sqlx::{query_file, query_file_as};
+use std::time::Duration;
+
+/// ESI only returns the journal of the last 30 days. Raffles a bit older can still settle in it.
+const RECONCILE_WINDOW: chrono::Duration = chrono::Duration::days(35);
+
+/// Matches Hypernet wallet journal entries to raffles, so we know the ISK that really moved.
+pub struct ReconcileWalletTask;
+
+#[async_trait]
+impl CronTask for ReconcileWalletTask {
+    fn name(&self) -> &'static str {
+        "ReconcileWalletTask"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(3600)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(120)
+    }
+
+    async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
+        let all_chars: Vec<EvECharacterInfo> = query_file_as!(
+            EvECharacterInfo,
+            "./sql/eve_character/select_all_characters.sql"
+        )
+        .fetch_all(&ctx.postgres)
+        .await?;
+
+        for char in all_chars {
+            if let Err(e) = reconcile_character(&ctx, &char).await {
+                warn!(
+                    "Error reconciling wallet of character {}: {:?}",
+                    char.character_id, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn reconcile_character(ctx: &CronAppContext, char: &EvECharacterInfo) -> anyhow::Result<()> {
+    let raffles: Vec<ReconcileRaffle> = query_file_as!(
+        ReconcileRaffle,
+        "./sql/hypernet_raffle/select_recent_raffles_for_character.sql",
+        char.character_id,
+        Utc::now() - RECONCILE_WINDOW
+    )
+    .fetch_all(&ctx.postgres)
+    .await?;
+    if raffles.is_empty() {
+        return Ok(());
+    }
+
+    let mut esi = ctx.esi.clone();
+    esi.use_refresh_token(&char.refresh_token).await?;
+    let journal = get_wallet_journal(&esi, char.character_id).await?;
+
+    let mut matched = 0;
+    let mut transaction = ctx.postgres.begin().await?;
+    for entry in journal.iter().filter(|x| x.is_hypernet()) {
+        let Some((raffle, kind)) = match_entry(&raffles, entry) else {
+            continue;
+        };
+
+        let inserted = query_file!(
+            "./sql/raffle_wallet_entries/insert_entry.sql",
+            entry.id,
+            raffle.raffle_id,
+            char.character_id,
+            kind as RaffleWalletEntryKind,
+            entry.amount,
+            entry.ref_type,
+            entry.date()
+        )
+        .execute(&mut *transaction)
+        .await?;
+        matched += inserted.rows_affected();
+    }
+    transaction.commit().await?;
+
+    debug!(
+        "Matched {} new journal entries for character {}",
+        matched, char.character_id
+    );
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::context::CronAppContext;
+use crate::cron::cost_basis_task::sync_wallet_transactions;
+use crate::cron::CronTask;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::hypernet_raffle_model::{HypernetRaffleResult, HypernetRaffleStatus};
+use crate::database::raffle_event::RaffleEventKind;
+use crate::hypernet::results::{held_quantity, infer_result};
+use crate::notify::enqueue_result;
+use async_trait::async_trait;
+use chrono::Utc;
+use log::{debug, warn};
+use serde_json::json;
+use sqlx::{query_file, query_file_as, query_file_scalar};
+use std::time::Duration;
+
+/// Works out whether finished raffles were won or lost, so users don't have to click the buttons.
+/// A result set through the buttons is never changed by this task.
+pub struct ResolveRaffleResultsTask;
+
+#[async_trait]
+impl CronTask for ResolveRaffleResultsTask {
+    fn name(&self) -> &'static str {
+        "ResolveRaffleResultsTask"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(900)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(120)
+    }
+
+    async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
+        let unresolved = query_file!("./sql/hypernet_raffle/select_unresolved_raffles.sql")
+            .fetch_all(&ctx.postgres)
+            .await?;
+
+        for raffles in unresolved.chunk_by(|a, b| a.character_id == b.character_id) {
+            let character_id = raffles[0].character_id;
+            let character: EvECharacterInfo = query_file_as!(
+                EvECharacterInfo,
+                "./sql/eve_character/select_character_by_id.sql",
+                character_id
+            )
+            .fetch_one(&ctx.postgres)
+            .await?;
+
+            let mut esi = ctx.esi.clone();
+            if let Err(e) = esi.use_refresh_token(&character.refresh_token).await {
+                warn!(
+                    "Error refreshing token for character {}: {:?}",
+                    character_id, e
+                );
+                continue;
+            }
+            let assets = match esi.group_assets().get_character_assets(character_id).await {
+                Ok(assets) => assets,
+                Err(e) => {
+                    warn!(
+                        "Error fetching assets for character {}: {:?}",
+                        character_id, e
+                    );
+                    continue;
+                }
+            };
+
+            // Trades of the raffled type since its creation make the baseline meaningless.
+            if let Err(e) = sync_wallet_transactions(&ctx.postgres, &esi, character_id).await {
+                warn!(
+                    "Error fetching wallet transactions for character {}: {:?}",
+                    character_id, e
+                );
+                continue;
+            }
+
+            let now = Utc::now();
+            for raffle in raffles {
+                let traded: bool = query_file_scalar!(
+                    "./sql/wallet_transactions/exists_for_type_since.sql",
+                    character_id,
+                    raffle.type_id,
+                    raffle.created_at
+                )
+                .fetch_one(&ctx.postgres)
+                .await?;
+                let baseline = Some(raffle.asset_baseline).filter(|_| !traded);
+                let held = held_quantity(&assets, raffle.type_id, raffle.location_id);
+                let Some(result) = infer_result(baseline, held, raffle.finished_at, now) else {
+                    continue;
+                };
+
+                debug!("Detected result {} for raffle {}", result, raffle.raffle_id);
+                let mut transaction = ctx.postgres.begin().await?;
+                let updated = query_file!(
+                    "./sql/hypernet_raffle/update_result_detected.sql",
+                    raffle.raffle_id,
+                    raffle.character_id,
+                    result as HypernetRaffleResult,
+                )
+                .execute(&mut *transaction)
+                .await?;
+                if updated.rows_affected() == 0 {
+                    continue;
+                }
+                query_file!(
+                    "./sql/raffle_events/insert_event.sql",
+                    raffle.raffle_id,
+                    raffle.character_id,
+                    RaffleEventKind::ResultSet as RaffleEventKind,
+                    None as Option<HypernetRaffleStatus>,
+                    Some(result) as Option<HypernetRaffleResult>,
+                    None as Option<i64>,
+                    None as Option<i64>,
+                    Some(
+                        json!({
+                            "source": "assets",
+                            "held": held,
+                            "asset_baseline": raffle.asset_baseline,
+                        })
+                        .to_string()
+                    ),
+                    now,
+                )
+                .execute(&mut *transaction)
+                .await?;
+                enqueue_result(&mut transaction, &raffle.raffle_id, raffle.character_id).await?;
+                transaction.commit().await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use crate::context::CronAppContext;
+use crate::cron::CronTask;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::database::notification_preference::NotificationEventKind;
+use crate::hypernet::types::type_name;
+use crate::notify::{enqueue_event, NotificationEvent};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::debug;
+use serenity::all::{
+    ButtonStyle, Colour, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateMessage,
+};
+use sqlx::{query_file, query_file_as};
+use std::env;
+use std::time::Duration;
+use thousands::Separable;
+
+/// Reminds owners of finished raffles that still have no result to pick one. The gap between
+/// reminders doubles every time, and after the last one the raffle is left alone.
+pub struct ResultReminderTask;
+
+/// When reminders go out, read from the environment.
+#[derive(Debug, Clone, Copy)]
+struct ReminderSchedule {
+    /// Time from finishing to the first reminder, doubled for every further one.
+    delay: Duration,
+    /// Reminders sent per raffle at most.
+    max_reminders: i32,
+}
+
+impl ReminderSchedule {
+    /// Reads `RESULT_REMINDER_DELAY_HOURS` and `RESULT_REMINDER_MAX`, defaulting to a first
+    /// reminder after a day and three reminders in total.
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(ReminderSchedule {
+            delay: match env::var("RESULT_REMINDER_DELAY_HOURS") {
+                Ok(x) => Duration::from_secs(x.parse::<u64>()?.saturating_mul(60 * 60)),
+                Err(_) => Duration::from_secs(24 * 60 * 60),
+            },
+            max_reminders: match env::var("RESULT_REMINDER_MAX") {
+                Ok(x) => x.parse()?,
+                Err(_) => 3,
+            },
+        })
+    }
+
+    /// When the next reminder of a raffle is due. `None` once the gap no longer fits into a
+    /// date, then the raffle is not reminded anymore.
+    fn next_reminder_at(
+        &self,
+        finished_at: DateTime<Utc>,
+        last_reminder_at: Option<DateTime<Utc>>,
+        reminders: i32,
+    ) -> Option<DateTime<Utc>> {
+        let (from, gap) = match last_reminder_at {
+            Some(last) => (
+                last,
+                self.delay
+                    .checked_mul(2u32.checked_pow(reminders.max(0) as u32)?)?,
+            ),
+            None => (finished_at, self.delay),
+        };
+        from.checked_add_signed(chrono::Duration::from_std(gap).ok()?)
+    }
+}
+
+#[async_trait]
+impl CronTask for ResultReminderTask {
+    fn name(&self) -> &'static str {
+        "ResultReminderTask"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(3600)
+    }
+
+    async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
+        let schedule = ReminderSchedule::from_env()?;
+        let candidates = query_file!(
+            "./sql/hypernet_raffle/select_reminder_candidates.sql",
+            schedule.max_reminders
+        )
+        .fetch_all(&ctx.postgres)
+        .await?;
+
+        let now = Utc::now();
+        for candidate in candidates {
+            let Some(due) = schedule.next_reminder_at(
+                candidate.finished_at,
+                candidate.last_result_reminder_at,
+                candidate.result_reminders,
+            ) else {
+                continue;
+            };
+            if due > now {
+                continue;
+            }
+
+            let raffle: EvEHypernetRaffle = query_file_as!(
+                EvEHypernetRaffle,
+                "./sql/hypernet_raffle/select_raffle_by_id.sql",
+                candidate.raffle_id,
+                candidate.character_id
+            )
+            .fetch_one(&ctx.postgres)
+            .await?;
+            let character: EvECharacterInfo = query_file_as!(
+                EvECharacterInfo,
+                "./sql/eve_character/select_character_by_id.sql",
+                raffle.character_id
+            )
+            .fetch_one(&ctx.postgres)
+            .await?;
+
+            debug!(
+                "Sending result reminder {} for raffle {}",
+                candidate.result_reminders + 1,
+                raffle.raffle_id
+            );
+            let message = build_reminder(&ctx, &raffle, candidate.finished_at).await?;
+            let event = NotificationEvent::new(
+                NotificationEventKind::ResultReminder,
+                &character,
+                Some(&raffle),
+                &message,
+            )?;
+
+            let mut transaction = ctx.postgres.begin().await?;
+            enqueue_event(&mut transaction, &event).await?;
+            query_file!(
+                "./sql/hypernet_raffle/mark_result_reminded.sql",
+                raffle.raffle_id,
+                raffle.character_id,
+                now
+            )
+            .execute(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn build_reminder(
+    ctx: &CronAppContext,
+    raffle: &EvEHypernetRaffle,
+    finished_at: DateTime<Utc>,
+) -> anyhow::Result<CreateMessage> {
+    let item_name = type_name(&ctx.postgres, &ctx.esi, raffle.type_id).await?;
+    let description = match raffle.role {
+        HypernetRaffleRole::Owner => "Your Hypernet Raffle finished, but nobody told me who won.",
+        HypernetRaffleRole::Participant => {
+            "A Hypernet Raffle you bought tickets in finished, but nobody told me who won."
+        }
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!(
+            "Hypernet Raffle {}",
+            HypernetRaffleStatus::Finished
+        ))
+        .description(description)
+        .thumbnail(format!(
+            "https://images.evetech.net/types/{}/icon",
+            raffle.type_id
+        ))
+        .color(Colour::from((0, 255, 0)))
+        .field("Item", item_name, true)
+        .field(
+            "Finished",
+            format!("<t:{}:R>", finished_at.timestamp()),
+            true,
+        )
+        .field(
+            "Ticket Count",
+            raffle.ticket_count.separate_with_dots(),
+            true,
+        )
+        .footer(CreateEmbedFooter::new(format!(
+            "RaffleID: {}",
+            raffle.raffle_id
+        )));
+
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("raffle-won:{}", raffle.key()))
+            .style(ButtonStyle::Success)
+            .label("Won Raffle"),
+        CreateButton::new(format!("raffle-lost:{}", raffle.key()))
+            .style(ButtonStyle::Danger)
+            .label("Lost Raffle"),
+    ]);
+    let bulk = CreateActionRow::Buttons(vec![CreateButton::new(format!(
+        "raffle-lost-all:{}",
+        raffle.key()
+    ))
+    .style(ButtonStyle::Secondary)
+    .label("Mark All Unresolved as Lost")]);
+
+    Ok(CreateMessage::new()
+        .content("Reminder: which way did this raffle go?")
+        .embed(embed)
+        .components(vec![buttons, bulk]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cron::result_reminder_task::ReminderSchedule;
+    use chrono::{TimeZone, Utc};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn next_reminder_test() {
+        let schedule = ReminderSchedule {
+            delay: Duration::from_secs(3600),
+            max_reminders: 3,
+        };
+        let finished_at = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_reminder_at(finished_at, None, 0),
+            Utc.with_ymd_and_hms(2025, 1, 1, 1, 0, 0).single()
+        );
+        let reminded_at = Utc.with_ymd_and_hms(2025, 1, 1, 1, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_reminder_at(finished_at, Some(reminded_at), 1),
+            Utc.with_ymd_and_hms(2025, 1, 1, 3, 0, 0).single()
+        );
+        assert_eq!(
+            schedule.next_reminder_at(finished_at, Some(reminded_at), 2),
+            Utc.with_ymd_and_hms(2025, 1, 1, 5, 0, 0).single()
+        );
+
+        // Far out gaps stop the reminders instead of overflowing.
+        let daily = ReminderSchedule {
+            delay: Duration::from_secs(24 * 60 * 60),
+            max_reminders: 100,
+        };
+        assert_eq!(
+            daily.next_reminder_at(finished_at, Some(reminded_at), 27),
+            None
+        );
+        assert_eq!(
+            daily.next_reminder_at(finished_at, Some(reminded_at), 40),
+            None
+        );
+    }
+}
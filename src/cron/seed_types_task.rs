@@ -0,0 +1,31 @@
+use crate::context::CronAppContext;
+use crate::cron::CronTask;
+use crate::hypernet::types::seed_types;
+use async_trait::async_trait;
+use log::info;
+use std::time::Duration;
+
+/// Fills the type name cache from ESI, so item autocompletion does not depend on the types we
+/// happened to look up before. The first run stores everything, later ones only new types.
+pub struct SeedTypesTask;
+
+#[async_trait]
+impl CronTask for SeedTypesTask {
+    fn name(&self) -> &'static str {
+        "SeedTypesTask"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(24 * 60 * 60)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(15 * 60)
+    }
+
+    async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
+        let seeded = seed_types(&ctx.postgres, &ctx.esi).await?;
+        info!("Seeded {} types", seeded);
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use crate::context::CronAppContext;
+use crate::cron::CronTask;
+use crate::hypernet::fill_model::{load_fill_samples, FillModel};
+use async_trait::async_trait;
+use log::info;
+use std::time::Duration;
+
+/// Below this many finished or expired owned raffles the model would mostly learn noise.
+const MIN_TRAINING_SAMPLES: usize = 20;
+
+/// Retrains the fill-probability model from the raffles we owned and have seen finish or expire.
+/// Placeholders and raffles we only bought tickets in are left out.
+pub struct TrainFillModelTask;
+
+#[async_trait]
+impl CronTask for TrainFillModelTask {
+    fn name(&self) -> &'static str {
+        "TrainFillModelTask"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(24 * 60 * 60)
+    }
+
+    async fn run(&self, ctx: CronAppContext) -> anyhow::Result<()> {
+        let samples = load_fill_samples(&ctx.postgres).await?;
+        if samples.len() < MIN_TRAINING_SAMPLES {
+            info!(
+                "Only {} raffles to train the fill model on, skipping",
+                samples.len()
+            );
+            return Ok(());
+        }
+
+        // Only the latest model is ever loaded, so older ones are dropped along with the insert.
+        let model = FillModel::train(&samples);
+        let mut transaction = ctx.postgres.begin().await?;
+        let model_id = sqlx::query_file_scalar!(
+            "./sql/fill_model/insert_model.sql",
+            samples.len() as i32,
+            serde_json::to_string(&model)?
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+        sqlx::query_file!("./sql/fill_model/delete_old_models.sql", model_id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
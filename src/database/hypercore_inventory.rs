@@ -0,0 +1,16 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Hypercores a character holds, as of the last inventory check.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct HypercoreInventory {
+    pub character_id: i32,
+    pub quantity: i32,
+    /// Average price paid for the held cores, as far as we saw them being bought.
+    pub average_cost: Option<f64>,
+    /// Cores burned per day over the recent listing history.
+    pub daily_usage: f64,
+    /// Set once the low-core alert was sent, cleared when the stock recovers.
+    pub low_alert_sent: bool,
+    pub updated_at: chrono::DateTime<Utc>,
+}
@@ -2,7 +2,7 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
-#[derive(Deserialize, Serialize, Debug, sqlx::Type, Clone)]
+#[derive(Deserialize, Serialize, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
 #[sqlx(type_name = "hypernet_raffle_status")]
 pub enum HypernetRaffleStatus {
     Created,
@@ -10,6 +10,20 @@ pub enum HypernetRaffleStatus {
     Finished,
 }
 
+impl HypernetRaffleStatus {
+    /// A raffle is created once and then either expires or finishes. Both are final.
+    pub fn can_transition_to(&self, next: HypernetRaffleStatus) -> bool {
+        matches!(
+            (self, next),
+            (HypernetRaffleStatus::Created, HypernetRaffleStatus::Expired)
+                | (
+                    HypernetRaffleStatus::Created,
+                    HypernetRaffleStatus::Finished
+                )
+        )
+    }
+}
+
 impl Display for HypernetRaffleStatus {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -20,7 +34,7 @@ impl Display for HypernetRaffleStatus {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, sqlx::Type, Clone)]
+#[derive(Deserialize, Serialize, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
 #[sqlx(type_name = "hypernet_raffle_result")]
 pub enum HypernetRaffleResult {
     None,
@@ -28,6 +42,35 @@ pub enum HypernetRaffleResult {
     Loser,
 }
 
+impl Display for HypernetRaffleResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HypernetRaffleResult::None => write!(f, "None"),
+            HypernetRaffleResult::Winner => write!(f, "Won"),
+            HypernetRaffleResult::Loser => write!(f, "Lost"),
+        }
+    }
+}
+
+/// Which side of the raffle our character is on.
+#[derive(Deserialize, Serialize, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "hypernet_raffle_role")]
+pub enum HypernetRaffleRole {
+    /// The character created the raffle.
+    Owner,
+    /// The character bought tickets in somebody else's raffle.
+    Participant,
+}
+
+impl Display for HypernetRaffleRole {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HypernetRaffleRole::Owner => write!(f, "Owner"),
+            HypernetRaffleRole::Participant => write!(f, "Participant"),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct EvEHypernetRaffle {
     pub location_id: i32,
@@ -42,7 +85,44 @@ pub struct EvEHypernetRaffle {
     pub hypercore_buy_price: Option<f64>,
     pub hypercore_sell_price: Option<f64>,
     pub plex_price: Option<f64>,
+    /// Tickets the character holds in the raffle, if known.
+    pub owned_tickets: Option<i32>,
+    /// We never saw the raffle being created, so the row was built from its expiry or finish
+    /// notification and the prices are from that time.
+    pub placeholder: bool,
     pub status: HypernetRaffleStatus,
     pub result: HypernetRaffleResult,
     pub created_at: chrono::DateTime<Utc>,
+    pub role: HypernetRaffleRole,
+    /// ISK the character paid for its tickets according to the wallet journal.
+    pub isk_spent: Option<f64>,
+    /// What the character paid for the item, matched FIFO from its wallet transactions.
+    pub item_cost_basis: Option<f64>,
+    /// Average price paid per Hypercore used for this raffle, matched FIFO like the item.
+    pub hypercore_unit_cost: Option<f64>,
+}
+
+impl EvEHypernetRaffle {
+    /// Identifies the raffle row in button and menu ids. The owner and a participant of one raffle
+    /// are tracked separately, so the raffle id alone is not enough.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.raffle_id, self.character_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::hypernet_raffle_model::HypernetRaffleStatus::{
+        Created, Expired, Finished,
+    };
+
+    #[tokio::test]
+    async fn status_transition_test() {
+        assert!(Created.can_transition_to(Expired));
+        assert!(Created.can_transition_to(Finished));
+        assert!(!Created.can_transition_to(Created));
+        assert!(!Expired.can_transition_to(Finished));
+        assert!(!Finished.can_transition_to(Expired));
+        assert!(!Finished.can_transition_to(Created));
+    }
 }
@@ -1,2 +1,13 @@
 pub mod eve_character_info;
+pub mod hypercore_inventory;
 pub mod hypernet_raffle_model;
+pub mod notification_digest;
+pub mod notification_preference;
+pub mod opportunity_watchlist;
+pub mod outbox_message;
+pub mod processed_notification;
+pub mod raffle_event;
+pub mod raffle_wallet_entry;
+pub mod raw_notification;
+pub mod wallet_transaction;
+pub mod webhook_endpoint;
@@ -0,0 +1,13 @@
+use crate::database::notification_preference::NotificationEventKind;
+use serde::{Deserialize, Serialize};
+
+/// One outbox message bundled into a digest.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DigestEntry {
+    pub id: i64,
+    pub raffle_id: String,
+    pub event_kind: NotificationEventKind,
+    pub character_id: i32,
+    pub character_name: String,
+    pub attempts: i32,
+}
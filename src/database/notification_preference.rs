@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+#[derive(
+    Deserialize, Serialize, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter,
+)]
+#[sqlx(type_name = "notification_event_kind")]
+pub enum NotificationEventKind {
+    #[name = "Raffle created"]
+    Created,
+    #[name = "Raffle expired"]
+    Expired,
+    #[name = "Raffle finished"]
+    Finished,
+    #[name = "Low on Hypercores"]
+    LowHypercores,
+    /// A raffle was marked as won or lost. Only delivered to backends picked for it.
+    #[name = "Raffle result set"]
+    ResultSet,
+    /// A finished raffle still has no result.
+    #[name = "Result reminder"]
+    ResultReminder,
+}
+
+impl NotificationEventKind {
+    pub const ALL: [NotificationEventKind; 6] = [
+        NotificationEventKind::Created,
+        NotificationEventKind::Expired,
+        NotificationEventKind::Finished,
+        NotificationEventKind::ResultSet,
+        NotificationEventKind::ResultReminder,
+        NotificationEventKind::LowHypercores,
+    ];
+}
+
+impl Display for NotificationEventKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotificationEventKind::Created => write!(f, "Raffle created"),
+            NotificationEventKind::Expired => write!(f, "Raffle expired"),
+            NotificationEventKind::Finished => write!(f, "Raffle finished"),
+            NotificationEventKind::LowHypercores => write!(f, "Low on Hypercores"),
+            NotificationEventKind::ResultSet => write!(f, "Raffle result set"),
+            NotificationEventKind::ResultReminder => write!(f, "Result reminder"),
+        }
+    }
+}
+
+#[derive(
+    Deserialize, Serialize, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter,
+)]
+#[sqlx(type_name = "notification_backend")]
+pub enum NotificationBackend {
+    /// The configured notification channel, a DM if none is set.
+    #[name = "Discord channel"]
+    DiscordChannel,
+    #[name = "Discord DM"]
+    DirectMessage,
+    #[name = "Webhook"]
+    Webhook,
+    #[name = "EVE mail"]
+    EveMail,
+}
+
+impl Display for NotificationBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotificationBackend::DiscordChannel => write!(f, "Discord channel"),
+            NotificationBackend::DirectMessage => write!(f, "Discord DM"),
+            NotificationBackend::Webhook => write!(f, "Webhook"),
+            NotificationBackend::EveMail => write!(f, "EVE mail"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NotificationBackendPreference {
+    pub event_kind: NotificationEventKind,
+    pub backend: NotificationBackend,
+}
+
+/// How a character wants to hear about one kind of event.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NotificationPreference {
+    pub character_id: i32,
+    pub event_kind: NotificationEventKind,
+    pub enabled: bool,
+    /// Overrides the notification channel of the user.
+    pub channel_id: Option<i64>,
+    /// Skip raffles whose item sells for less.
+    pub min_value: Option<f64>,
+    /// Skip raffles with a lower expected value.
+    pub min_expected_value: Option<f64>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl NotificationPreference {
+    /// The preference of a character that never changed it.
+    pub fn default_for(character_id: i32, event_kind: NotificationEventKind) -> Self {
+        NotificationPreference {
+            character_id,
+            event_kind,
+            enabled: true,
+            channel_id: None,
+            min_value: None,
+            min_expected_value: None,
+            updated_at: chrono::Utc::now(),
+        }
+    }
+}
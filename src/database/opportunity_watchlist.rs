@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// A type or a whole market group the opportunity scanner checks.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OpportunityWatchlistEntry {
+    pub id: i32,
+    pub type_id: Option<i32>,
+    pub market_group_id: Option<i32>,
+}
@@ -0,0 +1,37 @@
+use crate::database::notification_preference::{NotificationBackend, NotificationEventKind};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "outbox_state")]
+pub enum OutboxState {
+    Pending,
+    /// Claimed by the sender until `next_attempt_at`.
+    Sending,
+    Sent,
+    /// Gave up after too many attempts or because the user can't be reached at all.
+    Failed,
+}
+
+/// A notification waiting to be delivered through one backend by the outbox sender.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct OutboxMessage {
+    pub id: i64,
+    pub character_id: i32,
+    pub raffle_id: Option<String>,
+    pub event_kind: NotificationEventKind,
+    pub backend: NotificationBackend,
+    /// The webhook a `Webhook` delivery goes to. Gone if the webhook was removed.
+    pub webhook_id: Option<i64>,
+    /// The channel a `DiscordChannel` delivery goes to instead of the user's channel.
+    pub channel_id: Option<i64>,
+    /// The serialized `NotificationEvent`.
+    pub payload: String,
+    pub state: OutboxState,
+    pub attempts: i32,
+    /// When the message is due, or when the claim of a `Sending` message runs out.
+    pub next_attempt_at: chrono::DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: chrono::DateTime<Utc>,
+    pub sent_at: Option<chrono::DateTime<Utc>>,
+}
@@ -0,0 +1,24 @@
+use crate::database::hypernet_raffle_model::HypernetRaffleStatus;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "processed_notification_state")]
+pub enum ProcessedNotificationState {
+    /// The raffle transition is stored, but the Discord message has not been sent yet.
+    Pending,
+    Delivered,
+}
+
+/// An ESI notification we acted on. Makes sure each one results in exactly one Discord message.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ProcessedNotification {
+    pub notification_id: i64,
+    pub character_id: i32,
+    pub raffle_id: String,
+    pub notification_type: String,
+    pub status: HypernetRaffleStatus,
+    pub state: ProcessedNotificationState,
+    pub created_at: chrono::DateTime<Utc>,
+    pub delivered_at: Option<chrono::DateTime<Utc>>,
+}
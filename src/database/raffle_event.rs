@@ -0,0 +1,48 @@
+use crate::database::hypernet_raffle_model::{HypernetRaffleResult, HypernetRaffleStatus};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+#[derive(Deserialize, Serialize, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "raffle_event_kind")]
+pub enum RaffleEventKind {
+    /// The raffle was first stored, either from its RaffleCreated notification or as a placeholder.
+    Created,
+    /// A notification moved the raffle to another status.
+    StatusChanged,
+    /// A Discord user set the result of the raffle.
+    ResultSet,
+    /// Market prices as they were stored for the raffle.
+    PriceSnapshot,
+    /// Stored data was changed after the fact, by a user or by reprocessing.
+    Correction,
+}
+
+impl Display for RaffleEventKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RaffleEventKind::Created => write!(f, "Created"),
+            RaffleEventKind::StatusChanged => write!(f, "Status Changed"),
+            RaffleEventKind::ResultSet => write!(f, "Result Set"),
+            RaffleEventKind::PriceSnapshot => write!(f, "Price Snapshot"),
+            RaffleEventKind::Correction => write!(f, "Correction"),
+        }
+    }
+}
+
+/// One entry of the append-only history of a raffle.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RaffleEvent {
+    pub id: i64,
+    pub raffle_id: String,
+    pub character_id: i32,
+    pub kind: RaffleEventKind,
+    pub status: Option<HypernetRaffleStatus>,
+    pub result: Option<HypernetRaffleResult>,
+    pub notification_id: Option<i64>,
+    pub discord_user_id: Option<i64>,
+    /// JSON object with kind specific data, e.g. the prices of a snapshot.
+    pub details: Option<String>,
+    pub occurred_at: chrono::DateTime<Utc>,
+    pub recorded_at: chrono::DateTime<Utc>,
+}
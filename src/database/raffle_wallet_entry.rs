@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+#[derive(Deserialize, Serialize, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "raffle_wallet_entry_kind")]
+pub enum RaffleWalletEntryKind {
+    TicketPurchase,
+    /// Ticket sales paid to the owner of a raffle that sold out.
+    Payout,
+    Tax,
+    /// Tickets paid back after the raffle expired.
+    Refund,
+}
+
+impl Display for RaffleWalletEntryKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RaffleWalletEntryKind::TicketPurchase => write!(f, "Ticket Purchase"),
+            RaffleWalletEntryKind::Payout => write!(f, "Payout"),
+            RaffleWalletEntryKind::Tax => write!(f, "Tax"),
+            RaffleWalletEntryKind::Refund => write!(f, "Refund"),
+        }
+    }
+}
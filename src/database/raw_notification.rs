@@ -0,0 +1,14 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// A raffle-related ESI notification exactly as we received it.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RawNotification {
+    pub notification_id: i64,
+    pub character_id: i32,
+    pub notification_type: String,
+    pub timestamp: chrono::DateTime<Utc>,
+    pub sender_id: i32,
+    pub sender_type: String,
+    pub text: Option<String>,
+}
@@ -0,0 +1,14 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// A market transaction of a character, kept beyond the 30 days ESI remembers.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StoredWalletTransaction {
+    pub transaction_id: i64,
+    pub character_id: i32,
+    pub type_id: i32,
+    pub quantity: i32,
+    pub unit_price: f64,
+    pub is_buy: bool,
+    pub date: chrono::DateTime<Utc>,
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// A URL raffle events are posted to. It belongs either to a user or to a guild.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WebhookEndpoint {
+    pub id: i64,
+    pub discord_user_id: Option<i64>,
+    pub guild_id: Option<i64>,
+    pub url: String,
+    /// Key of the HMAC signature sent along with every delivery.
+    pub secret: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One attempt of posting to a webhook.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub outbox_id: Option<i64>,
+    pub event: String,
+    /// Missing if the request failed before the webhook answered.
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+    pub attempted_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,17 @@
+use rfesi::prelude::{Esi, EsiResult, RequestType};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct MarketGroupInfo {
+    pub name: String,
+    pub types: Vec<i32>,
+}
+
+/// rfesi does not expose market groups, so we query the endpoint ourselves.
+pub async fn get_market_group(esi: &Esi, market_group_id: i32) -> EsiResult<MarketGroupInfo> {
+    let path = esi
+        .get_endpoint_for_op_id("get_markets_groups_market_group_id")?
+        .replace("{market_group_id}", &market_group_id.to_string());
+    esi.query("GET", RequestType::Public, &path, None, None)
+        .await
+}
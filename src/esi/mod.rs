@@ -0,0 +1,47 @@
+pub mod market;
+pub mod universe;
+pub mod wallet_journal;
+pub mod wallet_transactions;
+
+use rfesi::prelude::{Esi, EsiError, EsiResult, RequestType};
+use serde::de::DeserializeOwned;
+
+/// Fetches every page of a paginated endpoint. rfesi hides the `X-Pages` header, so paging stops
+/// at the first page shorter than `page_size` or at the 404 ESI answers past the last page.
+pub async fn get_all_pages<T: DeserializeOwned>(
+    esi: &Esi,
+    request_type: RequestType,
+    path: &str,
+    page_size: usize,
+) -> EsiResult<Vec<T>> {
+    // RequestType is neither Copy nor Clone, so it is rebuilt for every page.
+    let authenticated = request_type == RequestType::Authenticated;
+    let mut items = vec![];
+    for page in 1.. {
+        let request_type = match authenticated {
+            true => RequestType::Authenticated,
+            false => RequestType::Public,
+        };
+        let page_param = page.to_string();
+        let mut batch: Vec<T> = match esi
+            .query(
+                "GET",
+                request_type,
+                path,
+                Some(&[("page", page_param.as_str())]),
+                None,
+            )
+            .await
+        {
+            Ok(batch) => batch,
+            Err(EsiError::InvalidStatusCode(404)) if page > 1 => break,
+            Err(e) => return Err(e),
+        };
+        let last = batch.len() < page_size;
+        items.append(&mut batch);
+        if last {
+            break;
+        }
+    }
+    Ok(items)
+}
@@ -0,0 +1,60 @@
+use crate::esi::get_all_pages;
+use rfesi::prelude::{Esi, EsiResult, RequestType};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct NamedId {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct InventoryTypeIds {
+    inventory_types: Option<Vec<NamedId>>,
+}
+
+/// rfesi's `get_ids` drops inventory types from the response, so we query the endpoint ourselves.
+pub async fn get_inventory_type_ids(esi: &Esi, names: &[&str]) -> EsiResult<Vec<NamedId>> {
+    let path = esi.get_endpoint_for_op_id("post_universe_ids")?;
+    let body = serde_json::to_string(names)?;
+    let ids: InventoryTypeIds = esi
+        .query("POST", RequestType::Public, &path, None, Some(&body))
+        .await?;
+    Ok(ids.inventory_types.unwrap_or_default())
+}
+
+/// Type ids per page of `/universe/types`.
+const TYPE_IDS_PAGE_SIZE: usize = 1000;
+/// Ids `/universe/names` resolves per request at most.
+pub const NAMES_BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+struct CategorizedName {
+    id: i32,
+    name: String,
+    category: String,
+}
+
+/// Every type id known to ESI, published or not.
+pub async fn get_all_type_ids(esi: &Esi) -> EsiResult<Vec<i32>> {
+    let path = esi.get_endpoint_for_op_id("get_universe_types")?;
+    get_all_pages(esi, RequestType::Public, &path, TYPE_IDS_PAGE_SIZE).await
+}
+
+/// Names of the given inventory types, at most [`NAMES_BATCH_SIZE`] per call. rfesi has no
+/// binding for `/universe/names`.
+pub async fn get_inventory_type_names(esi: &Esi, type_ids: &[i32]) -> EsiResult<Vec<NamedId>> {
+    let path = esi.get_endpoint_for_op_id("post_universe_names")?;
+    let body = serde_json::to_string(type_ids)?;
+    let names: Vec<CategorizedName> = esi
+        .query("POST", RequestType::Public, &path, None, Some(&body))
+        .await?;
+    Ok(names
+        .into_iter()
+        .filter(|x| x.category == "inventory_type")
+        .map(|x| NamedId {
+            id: x.id,
+            name: x.name,
+        })
+        .collect())
+}
@@ -0,0 +1,71 @@
+use crate::esi::get_all_pages;
+use rfesi::prelude::{Esi, EsiResult, RequestType};
+use serde::Deserialize;
+
+/// Journal entries per page of the wallet journal.
+const JOURNAL_PAGE_SIZE: usize = 2500;
+
+/// ESI books the Hypernet under its internal name "flux". None of these entries carry the raffle:
+/// raffle IDs are 128 bit hex strings and do not fit the numeric `context_id`.
+pub const HYPERNET_TICKET_SALE: &str = "flux_ticket_sale";
+pub const HYPERNET_TICKET_REPAYMENT: &str = "flux_ticket_repayment";
+pub const HYPERNET_PAYOUT: &str = "flux_payout";
+pub const HYPERNET_TAX: &str = "flux_tax";
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WalletJournalEntry {
+    pub amount: Option<f64>,
+    pub date: String,
+    pub description: String,
+    pub id: i64,
+    pub reason: Option<String>,
+    pub ref_type: String,
+}
+
+impl WalletJournalEntry {
+    /// Whether this entry was caused by the Hypernet (ticket purchases, payouts, tax, refunds).
+    pub fn is_hypernet(&self) -> bool {
+        [
+            HYPERNET_TICKET_SALE,
+            HYPERNET_TICKET_REPAYMENT,
+            HYPERNET_PAYOUT,
+            HYPERNET_TAX,
+        ]
+        .contains(&self.ref_type.as_str())
+    }
+
+    /// Whether the description or reason names the raffle. Case and the dashes of the UUID form
+    /// are ignored.
+    pub fn names_raffle(&self, raffle_id: &str) -> bool {
+        let raffle_id = normalize_id(raffle_id);
+        !raffle_id.is_empty()
+            && std::iter::once(self.description.as_str())
+                .chain(self.reason.as_deref())
+                .any(|x| normalize_id(x).contains(&raffle_id))
+    }
+
+    pub fn date(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc3339(&self.date)
+            .ok()
+            .map(|x| x.to_utc())
+    }
+}
+
+/// rfesi does not expose the wallet journal, so we query the endpoint ourselves. Every page is
+/// fetched, which covers the last 30 days.
+pub async fn get_wallet_journal(
+    esi: &Esi,
+    character_id: i32,
+) -> EsiResult<Vec<WalletJournalEntry>> {
+    let path = esi
+        .get_endpoint_for_op_id("get_characters_character_id_wallet_journal")?
+        .replace("{character_id}", &character_id.to_string());
+    get_all_pages(esi, RequestType::Authenticated, &path, JOURNAL_PAGE_SIZE).await
+}
+
+fn normalize_id(text: &str) -> String {
+    text.chars()
+        .filter(|x| *x != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
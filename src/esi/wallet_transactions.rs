@@ -0,0 +1,38 @@
+use rfesi::prelude::{Esi, EsiResult, RequestType};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(dead_code)]
+pub struct WalletTransaction {
+    pub client_id: i32,
+    pub date: String,
+    pub is_buy: bool,
+    pub is_personal: bool,
+    pub journal_ref_id: i64,
+    pub location_id: i64,
+    pub quantity: i32,
+    pub transaction_id: i64,
+    pub type_id: i32,
+    pub unit_price: f64,
+}
+
+impl WalletTransaction {
+    pub fn date(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc3339(&self.date)
+            .ok()
+            .map(|x| x.to_utc())
+    }
+}
+
+/// rfesi does not expose the wallet transactions, so we query the endpoint ourselves.
+/// ESI returns the most recent 2500 transactions of the last 30 days.
+pub async fn get_wallet_transactions(
+    esi: &Esi,
+    character_id: i32,
+) -> EsiResult<Vec<WalletTransaction>> {
+    let path = esi
+        .get_endpoint_for_op_id("get_characters_character_id_wallet_transactions")?
+        .replace("{character_id}", &character_id.to_string());
+    esi.query("GET", RequestType::Authenticated, &path, None, None)
+        .await
+}
@@ -1,15 +1,21 @@
 use crate::context::{AppContext, Error};
 use crate::database::eve_character_info::EvECharacterInfo;
 use crate::database::hypernet_raffle_model::{
-    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleStatus,
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
 };
+use crate::database::raffle_event::RaffleEventKind;
+use crate::notify::digest::{digest_entries, render_page};
+use crate::notify::discord::{correction_buttons, result_buttons, result_embeds};
+use crate::notify::enqueue_result;
+use chrono::Utc;
 use log::info;
 use serde_json::json;
 use serenity::all::{
-    ActivityData, ButtonStyle, Color, ComponentInteractionDataKind, Context, CreateActionRow,
-    CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
-    FullEvent, InteractionType,
+    ActivityData, ChannelId, ComponentInteraction, ComponentInteractionDataKind, Context,
+    CreateActionRow, CreateInteractionResponse, CreateInteractionResponseFollowup,
+    CreateInteractionResponseMessage, FullEvent, InteractionType, MessageId,
 };
+use sqlx::PgPool;
 
 pub async fn event_handler(
     ctx: &Context,
@@ -27,39 +33,52 @@ pub async fn event_handler(
         FullEvent::InteractionCreate { interaction } => {
             if let InteractionType::Component = interaction.kind() {
                 let interaction = interaction.as_message_component().unwrap();
+                if interaction.data.custom_id.starts_with("digest-") {
+                    return handle_digest(ctx, interaction, data).await;
+                }
                 if let ComponentInteractionDataKind::Button = interaction.data.kind {
                     if interaction.data.custom_id.starts_with("raffle-won:")
                         || interaction.data.custom_id.starts_with("raffle-lost:")
                         || interaction.data.custom_id.starts_with("open-market:")
+                        || interaction.data.custom_id.starts_with("raffle-lost-all:")
+                        || interaction.data.custom_id.starts_with("raffle-change:")
                     {
                         // Handle the win of a raffle
-                        let raffle_id = if interaction.data.custom_id.starts_with("raffle-won:") {
+                        let raffle_key = if interaction.data.custom_id.starts_with("raffle-won:") {
                             &interaction.data.custom_id["raffle-won:".len()..]
                         } else if interaction.data.custom_id.starts_with("raffle-lost:") {
                             &interaction.data.custom_id["raffle-lost:".len()..]
                         } else if interaction.data.custom_id.starts_with("open-market:") {
                             &interaction.data.custom_id["open-market:".len()..]
+                        } else if interaction.data.custom_id.starts_with("raffle-lost-all:") {
+                            &interaction.data.custom_id["raffle-lost-all:".len()..]
+                        } else if interaction.data.custom_id.starts_with("raffle-change:") {
+                            &interaction.data.custom_id["raffle-change:".len()..]
                         } else {
                             unreachable!()
                         };
 
-                        let raffle: EvEHypernetRaffle = sqlx::query_file_as!(
-                            EvEHypernetRaffle,
-                            "./sql/hypernet_raffle/select_raffle_by_id.sql",
-                            raffle_id
-                        )
-                        .fetch_one(&data.postgres)
-                        .await?;
-
-                        let character_info: EvECharacterInfo = sqlx::query_file_as!(
-                            EvECharacterInfo,
-                            "./sql/eve_character/select_character_by_id.sql",
-                            raffle.character_id
+                        let raffle = select_raffle(
+                            &data.postgres,
+                            raffle_key,
+                            interaction.user.id.get() as i64,
                         )
-                        .fetch_one(&data.postgres)
                         .await?;
+                        let character_info: Option<EvECharacterInfo> = match &raffle {
+                            Some(raffle) => Some(
+                                sqlx::query_file_as!(
+                                    EvECharacterInfo,
+                                    "./sql/eve_character/select_character_by_id.sql",
+                                    raffle.character_id
+                                )
+                                .fetch_one(&data.postgres)
+                                .await?,
+                            ),
+                            None => None,
+                        }
+                        .filter(|x| interaction.user.id == x.discord_user_id as u64);
 
-                        if interaction.user.id != character_info.discord_user_id as u64 {
+                        let (Some(raffle), Some(character_info)) = (raffle, character_info) else {
                             interaction
                                 .create_response(
                                     &ctx,
@@ -75,81 +94,112 @@ pub async fn event_handler(
                                 )
                                 .await?;
                             return Ok(());
-                        }
+                        };
 
                         let mut esi = data.esi.clone();
                         esi.use_refresh_token(&character_info.refresh_token).await?;
 
-                        if interaction.data.custom_id.starts_with("raffle-won:") {
-                            sqlx::query_file!(
-                                "./sql/hypernet_raffle/update_result.sql",
-                                raffle_id,
-                                HypernetRaffleResult::Winner as HypernetRaffleResult,
-                            )
-                            .execute(&data.postgres)
-                            .await?;
-
-                            let edited_embeds: Vec<CreateEmbed> = interaction
-                                .message
-                                .embeds
-                                .iter()
-                                .map(|embed| {
-                                    CreateEmbed::from(embed.clone())
-                                        .title(format!(
-                                            "{} - Won",
-                                            embed.title.clone().unwrap_or("".to_string())
-                                        ))
-                                        .color(Color::from_rgb(241, 196, 15))
-                                })
-                                .collect();
+                        if interaction.data.custom_id.starts_with("raffle-won:")
+                            || interaction.data.custom_id.starts_with("raffle-lost:")
+                        {
+                            let result = if interaction.data.custom_id.starts_with("raffle-won:") {
+                                HypernetRaffleResult::Winner
+                            } else {
+                                HypernetRaffleResult::Loser
+                            };
+                            // A stale message may offer the result the raffle already has
+                            if raffle.result != result {
+                                record_result(
+                                    &data.postgres,
+                                    &raffle,
+                                    result,
+                                    interaction.user.id.get() as i64,
+                                    Some((interaction.channel_id, interaction.message.id)),
+                                )
+                                .await?;
+                            }
 
                             interaction
                                 .create_response(
                                     &ctx,
                                     CreateInteractionResponse::UpdateMessage(
                                         CreateInteractionResponseMessage::new()
-                                            .embeds(edited_embeds)
+                                            .embeds(result_embeds(
+                                                &interaction.message.embeds,
+                                                result,
+                                            ))
                                             .components(vec![CreateActionRow::Buttons(
-                                                create_disabled_raffle_buttons(&raffle_id),
+                                                result_buttons(&raffle.key()),
                                             )]),
                                     ),
                                 )
                                 .await?;
-                        } else if interaction.data.custom_id.starts_with("raffle-lost:") {
-                            sqlx::query_file!(
-                                "./sql/hypernet_raffle/update_result.sql",
-                                raffle_id,
-                                HypernetRaffleResult::Loser as HypernetRaffleResult,
+                        } else if interaction.data.custom_id.starts_with("raffle-lost-all:") {
+                            // Everything of the user still waiting for a result, on any character
+                            let unresolved: Vec<EvEHypernetRaffle> = sqlx::query_file_as!(
+                                EvEHypernetRaffle,
+                                "./sql/hypernet_raffle/select_unresolved_for_user.sql",
+                                character_info.discord_user_id
                             )
-                            .execute(&data.postgres)
+                            .fetch_all(&data.postgres)
                             .await?;
-
-                            let edited_embeds: Vec<CreateEmbed> = interaction
-                                .message
-                                .embeds
-                                .iter()
-                                .map(|embed| {
-                                    CreateEmbed::from(embed.clone())
-                                        .title(format!(
-                                            "{} - Loss",
-                                            embed.title.clone().unwrap_or("".to_string())
-                                        ))
-                                        .color(Color::from_rgb(230, 126, 34))
-                                })
-                                .collect();
+                            // The raffle clicked on may have had its result already
+                            let shown = match raffle.result {
+                                HypernetRaffleResult::None => HypernetRaffleResult::Loser,
+                                result => result,
+                            };
+                            for unresolved_raffle in &unresolved {
+                                record_result(
+                                    &data.postgres,
+                                    unresolved_raffle,
+                                    HypernetRaffleResult::Loser,
+                                    interaction.user.id.get() as i64,
+                                    None,
+                                )
+                                .await?;
+                            }
 
                             interaction
                                 .create_response(
                                     &ctx,
                                     CreateInteractionResponse::UpdateMessage(
                                         CreateInteractionResponseMessage::new()
-                                            .embeds(edited_embeds)
+                                            .embeds(result_embeds(
+                                                &interaction.message.embeds,
+                                                shown,
+                                            ))
                                             .components(vec![CreateActionRow::Buttons(
-                                                create_disabled_raffle_buttons(raffle_id),
+                                                result_buttons(&raffle.key()),
                                             )]),
                                     ),
                                 )
                                 .await?;
+                            interaction
+                                .create_followup(
+                                    &ctx,
+                                    CreateInteractionResponseFollowup::new()
+                                        .ephemeral(true)
+                                        .content(format!(
+                                            "Marked {} unresolved raffles as lost.",
+                                            unresolved.len()
+                                        )),
+                                )
+                                .await?;
+                        } else if interaction.data.custom_id.starts_with("raffle-change:") {
+                            // Opens the result buttons again, the embed changes once one is picked
+                            interaction
+                                .create_response(
+                                    &ctx,
+                                    CreateInteractionResponse::UpdateMessage(
+                                        CreateInteractionResponseMessage::new().components(vec![
+                                            CreateActionRow::Buttons(correction_buttons(
+                                                &raffle.key(),
+                                                raffle.result,
+                                            )),
+                                        ]),
+                                    ),
+                                )
+                                .await?;
                         } else if interaction.data.custom_id.starts_with("open-market:") {
                             let http_client = reqwest::Client::new();
 
@@ -188,18 +238,162 @@ pub async fn event_handler(
     Ok(())
 }
 
-fn create_disabled_raffle_buttons(raffle_id: &str) -> Vec<CreateButton> {
-    vec![
-        CreateButton::new("raffle-won:".to_string() + raffle_id)
-            .label("Won Raffle")
-            .style(ButtonStyle::Success)
-            .disabled(true),
-        CreateButton::new("raffle-lost:".to_string() + raffle_id)
-            .label("Lost Raffle")
-            .style(ButtonStyle::Danger)
-            .disabled(true),
-        CreateButton::new("open-market:".to_string() + raffle_id)
-            .label("Open Market")
-            .style(ButtonStyle::Primary),
-    ]
+/// Turns the pages of a digest and sets the results picked from its menu.
+async fn handle_digest(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    data: &AppContext,
+) -> Result<(), Error> {
+    let mut parts = interaction.data.custom_id.split(':');
+    let action = parts.next().unwrap_or_default();
+    let digest_id: i64 = parts.next().unwrap_or_default().parse()?;
+    let page: usize = parts.next().unwrap_or_default().parse()?;
+
+    let owner: i64 = sqlx::query_file_scalar!(
+        "./sql/notification_digest/select_digest_owner.sql",
+        digest_id
+    )
+    .fetch_one(&data.postgres)
+    .await?;
+    if interaction.user.id != owner as u64 {
+        interaction
+            .create_response(
+                &ctx,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .ephemeral(true)
+                        .content("This is not your digest."),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    if let ("digest-result", ComponentInteractionDataKind::StringSelect { values }) =
+        (action, &interaction.data.kind)
+    {
+        for value in values {
+            let (result, raffle_key) = match value.split_once(':') {
+                Some(("won", raffle_key)) => (HypernetRaffleResult::Winner, raffle_key),
+                Some(("lost", raffle_key)) => (HypernetRaffleResult::Loser, raffle_key),
+                _ => continue,
+            };
+            let Some(raffle) = select_raffle(&data.postgres, raffle_key, owner).await? else {
+                continue;
+            };
+            // A stale menu may offer the result the raffle already has, a different one is
+            // recorded as a correction.
+            if raffle.result != result {
+                record_result(&data.postgres, &raffle, result, owner, None).await?;
+            }
+        }
+    }
+
+    let entries = digest_entries(&data.postgres, digest_id).await?;
+    let (embed, components) =
+        render_page(&data.postgres, &data.esi, digest_id, &entries, page).await?;
+    interaction
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .components(components),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// The raffle a button or menu option refers to by [`EvEHypernetRaffle::key`]. Messages sent
+/// before raffles were tracked per character only carry the raffle id, those are matched against
+/// the characters of the user clicking.
+async fn select_raffle(
+    postgres: &PgPool,
+    raffle_key: &str,
+    discord_user_id: i64,
+) -> Result<Option<EvEHypernetRaffle>, Error> {
+    let raffle = match raffle_key.split_once(':') {
+        Some((raffle_id, character_id)) => {
+            sqlx::query_file_as!(
+                EvEHypernetRaffle,
+                "./sql/hypernet_raffle/select_raffle_by_id.sql",
+                raffle_id,
+                character_id.parse::<i32>()?
+            )
+            .fetch_optional(postgres)
+            .await?
+        }
+        None => sqlx::query_file_as!(
+            EvEHypernetRaffle,
+            "./sql/hypernet_raffle/select_user_raffles_by_id.sql",
+            raffle_key,
+            discord_user_id,
+            None as Option<String>
+        )
+        .fetch_all(postgres)
+        .await?
+        .into_iter()
+        .next(),
+    };
+    Ok(raffle)
+}
+
+/// Stores the result and records who set it in the raffle history. Changing an earlier result is
+/// recorded as a correction along with the result it replaced.
+async fn record_result(
+    postgres: &PgPool,
+    raffle: &EvEHypernetRaffle,
+    result: HypernetRaffleResult,
+    discord_user_id: i64,
+    live_message: Option<(ChannelId, MessageId)>,
+) -> Result<(), Error> {
+    let (kind, details) = if raffle.result == HypernetRaffleResult::None {
+        (RaffleEventKind::ResultSet, None)
+    } else {
+        (
+            RaffleEventKind::Correction,
+            Some(json!({ "previous_result": raffle.result.to_string() }).to_string()),
+        )
+    };
+
+    let mut transaction = postgres.begin().await?;
+    sqlx::query_file!(
+        "./sql/hypernet_raffle/update_result.sql",
+        raffle.raffle_id,
+        raffle.character_id,
+        result as HypernetRaffleResult,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query_file!(
+        "./sql/raffle_events/insert_event.sql",
+        raffle.raffle_id,
+        raffle.character_id,
+        kind as RaffleEventKind,
+        None as Option<HypernetRaffleStatus>,
+        Some(result) as Option<HypernetRaffleResult>,
+        None as Option<i64>,
+        Some(discord_user_id),
+        details,
+        Utc::now(),
+    )
+    .execute(&mut *transaction)
+    .await?;
+    // The message the buttons were on follows the raffle from now on, unless it already has one
+    if let Some((channel_id, message_id)) = live_message {
+        sqlx::query_file!(
+            "./sql/hypernet_raffle/claim_live_message.sql",
+            raffle.raffle_id,
+            raffle.character_id,
+            channel_id.get() as i64,
+            message_id.get() as i64,
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+    enqueue_result(&mut transaction, &raffle.raffle_id, raffle.character_id).await?;
+    transaction.commit().await?;
+
+    Ok(())
 }
@@ -0,0 +1,330 @@
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleStatus,
+};
+use crate::database::wallet_transaction::StoredWalletTransaction;
+use crate::hypernet::prices::HYPERCORE_TYPE_ID;
+use crate::hypernet::profit::RaffleSetup;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+
+/// Something that takes units of a type out of the character's inventory.
+#[derive(Debug, Clone)]
+pub struct Consumption {
+    pub date: DateTime<Utc>,
+    pub quantity: i64,
+    /// Raffle the units went into. `None` for market sales.
+    pub raffle_id: Option<String>,
+    /// When the units came back to the inventory, as the raffle expired or its owner won it.
+    pub returned_at: Option<DateTime<Utc>>,
+}
+
+/// Quantity and unit price of a purchase, or of what is left of it.
+type Lot = (i64, f64);
+
+/// Outcome of matching the purchases of one type against everything that consumed it.
+#[derive(Debug, Default)]
+pub struct FifoMatch {
+    /// Average unit cost per raffle. Raffles that could not be fully covered by earlier purchases
+    /// are left out, as the character got the item some other way.
+    pub costs: HashMap<String, f64>,
+    /// Quantity and unit price of the purchases that are not used up yet, oldest first.
+    pub remaining: Vec<(i64, f64)>,
+}
+
+impl FifoMatch {
+    /// Average price paid for the units still held, as far as they were bought.
+    pub fn average_remaining_cost(&self) -> Option<f64> {
+        let quantity: i64 = self.remaining.iter().map(|(quantity, _)| quantity).sum();
+        if quantity == 0 {
+            return None;
+        }
+        let cost: f64 = self
+            .remaining
+            .iter()
+            .map(|(quantity, unit_price)| *quantity as f64 * unit_price)
+            .sum();
+        Some(cost / quantity as f64)
+    }
+}
+
+/// Matches consumptions against the purchases of one type, oldest purchase first. Units that
+/// come back are put in front of the queue with the price they were matched at, as they were
+/// bought before anything still queued.
+pub fn match_fifo(
+    purchases: &[StoredWalletTransaction],
+    consumptions: &[Consumption],
+) -> FifoMatch {
+    let mut purchases: Vec<&StoredWalletTransaction> =
+        purchases.iter().filter(|x| x.is_buy).collect();
+    purchases.sort_by_key(|x| (x.date, x.transaction_id));
+    let mut consumptions: Vec<&Consumption> = consumptions.iter().collect();
+    consumptions.sort_by_key(|x| x.date);
+
+    let mut lots: VecDeque<Lot> = VecDeque::new();
+    let mut next_purchase = purchases.into_iter().peekable();
+    let mut costs = HashMap::new();
+    // Lots taken by consumptions that are returned later, by the time they come back.
+    let mut returns: Vec<(DateTime<Utc>, Vec<Lot>)> = Vec::new();
+
+    for consumption in consumptions {
+        while let Some(purchase) = next_purchase.next_if(|x| x.date <= consumption.date) {
+            lots.push_back((purchase.quantity as i64, purchase.unit_price));
+        }
+        returns.sort_by_key(|(date, _)| *date);
+        let due = returns.partition_point(|(date, _)| *date <= consumption.date);
+        for (_, taken) in returns.drain(..due) {
+            for lot in taken.into_iter().rev() {
+                lots.push_front(lot);
+            }
+        }
+
+        let mut needed = consumption.quantity;
+        let mut cost = 0.0;
+        let mut taken_lots = Vec::new();
+        while needed > 0 {
+            let Some((quantity, unit_price)) = lots.front_mut() else {
+                break;
+            };
+            let taken = needed.min(*quantity);
+            cost += taken as f64 * *unit_price;
+            taken_lots.push((taken, *unit_price));
+            needed -= taken;
+            *quantity -= taken;
+            if *quantity == 0 {
+                lots.pop_front();
+            }
+        }
+        if let Some(returned_at) = consumption.returned_at {
+            returns.push((returned_at, taken_lots));
+        }
+
+        if let Some(raffle_id) = &consumption.raffle_id {
+            if needed == 0 {
+                costs.insert(raffle_id.clone(), cost / consumption.quantity.max(1) as f64);
+            }
+        }
+    }
+
+    lots.extend(next_purchase.map(|x| (x.quantity as i64, x.unit_price)));
+    returns.sort_by_key(|(date, _)| *date);
+    for (_, taken) in returns.into_iter().rev() {
+        for lot in taken.into_iter().rev() {
+            lots.push_front(lot);
+        }
+    }
+    FifoMatch {
+        costs,
+        remaining: lots.into_iter().collect(),
+    }
+}
+
+/// Market sales of a type, which take units out of the inventory like raffles do.
+fn sales(transactions: &[StoredWalletTransaction], type_id: i32) -> Vec<Consumption> {
+    transactions
+        .iter()
+        .filter(|x| x.type_id == type_id && !x.is_buy)
+        .map(|x| Consumption {
+            date: x.date,
+            quantity: x.quantity as i64,
+            raffle_id: None,
+            returned_at: None,
+        })
+        .collect()
+}
+
+fn purchases(
+    transactions: &[StoredWalletTransaction],
+    type_id: i32,
+) -> Vec<StoredWalletTransaction> {
+    transactions
+        .iter()
+        .filter(|x| x.type_id == type_id && x.is_buy)
+        .cloned()
+        .collect()
+}
+
+/// When the units a raffle took came back: the item returns when the raffle expires or its owner
+/// wins it, the Hypercores only when it expires. `ended_at` holds when the raffles ended.
+fn returned_at(
+    raffle: &EvEHypernetRaffle,
+    ended_at: &HashMap<String, DateTime<Utc>>,
+    cores: bool,
+) -> Option<DateTime<Utc>> {
+    let returned = match (raffle.status, raffle.result) {
+        (HypernetRaffleStatus::Expired, _) => true,
+        (HypernetRaffleStatus::Finished, HypernetRaffleResult::Winner) => !cores,
+        _ => false,
+    };
+    returned.then(|| {
+        ended_at
+            .get(&raffle.raffle_id)
+            .copied()
+            .unwrap_or(raffle.created_at)
+    })
+}
+
+/// Matches the character's Hypercore purchases against the cores its raffles burned and the
+/// ones it sold.
+pub fn hypercore_match(
+    raffles: &[EvEHypernetRaffle],
+    ended_at: &HashMap<String, DateTime<Utc>>,
+    transactions: &[StoredWalletTransaction],
+) -> FifoMatch {
+    let mut consumptions = sales(transactions, HYPERCORE_TYPE_ID);
+    consumptions.extend(raffles.iter().filter_map(|x| {
+        Some(Consumption {
+            date: x.created_at,
+            quantity: RaffleSetup::from(x).required_cores()? as i64,
+            raffle_id: Some(x.raffle_id.clone()),
+            returned_at: returned_at(x, ended_at, true),
+        })
+    }));
+
+    match_fifo(&purchases(transactions, HYPERCORE_TYPE_ID), &consumptions)
+}
+
+/// Cost basis of every raffle the character created: what it paid for the item and the average
+/// price of the Hypercores it burned.
+pub fn raffle_cost_basis(
+    raffles: &[EvEHypernetRaffle],
+    ended_at: &HashMap<String, DateTime<Utc>>,
+    transactions: &[StoredWalletTransaction],
+) -> HashMap<String, (Option<f64>, Option<f64>)> {
+    let mut item_costs: HashMap<String, f64> = HashMap::new();
+    let mut type_ids: Vec<i32> = raffles.iter().map(|x| x.type_id).collect();
+    type_ids.sort();
+    type_ids.dedup();
+    for type_id in type_ids {
+        let mut consumptions = sales(transactions, type_id);
+        consumptions.extend(
+            raffles
+                .iter()
+                .filter(|x| x.type_id == type_id)
+                .map(|x| Consumption {
+                    date: x.created_at,
+                    quantity: 1,
+                    raffle_id: Some(x.raffle_id.clone()),
+                    returned_at: returned_at(x, ended_at, false),
+                }),
+        );
+        item_costs.extend(match_fifo(&purchases(transactions, type_id), &consumptions).costs);
+    }
+
+    let core_costs = hypercore_match(raffles, ended_at, transactions).costs;
+
+    raffles
+        .iter()
+        .map(|x| {
+            (
+                x.raffle_id.clone(),
+                (
+                    item_costs.get(&x.raffle_id).copied(),
+                    core_costs.get(&x.raffle_id).copied(),
+                ),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::wallet_transaction::StoredWalletTransaction;
+    use crate::hypernet::cost_basis::{match_fifo, Consumption};
+    use chrono::{DateTime, Utc};
+
+    fn purchase(
+        id: i64,
+        date: DateTime<Utc>,
+        quantity: i32,
+        unit_price: f64,
+    ) -> StoredWalletTransaction {
+        StoredWalletTransaction {
+            transaction_id: id,
+            character_id: 1,
+            type_id: 1,
+            quantity,
+            unit_price,
+            is_buy: true,
+            date,
+        }
+    }
+
+    #[tokio::test]
+    async fn match_fifo_test() {
+        let start = Utc::now() - chrono::Duration::days(10);
+        let day = |x: i64| start + chrono::Duration::days(x);
+
+        let purchases = vec![
+            purchase(1, day(0), 2, 100.0),
+            purchase(2, day(2), 2, 200.0),
+            purchase(3, day(6), 1, 50.0),
+        ];
+        let consumptions = vec![
+            Consumption {
+                date: day(1),
+                quantity: 1,
+                raffle_id: Some("a".to_string()),
+                returned_at: None,
+            },
+            // Sold on the market, takes the remaining unit of the first lot.
+            Consumption {
+                date: day(3),
+                quantity: 1,
+                raffle_id: None,
+                returned_at: None,
+            },
+            Consumption {
+                date: day(4),
+                quantity: 2,
+                raffle_id: Some("b".to_string()),
+                returned_at: None,
+            },
+            // Nothing left to cover it, the purchase on day 6 comes too late.
+            Consumption {
+                date: day(5),
+                quantity: 1,
+                raffle_id: Some("c".to_string()),
+                returned_at: None,
+            },
+        ];
+
+        let matched = match_fifo(&purchases, &consumptions);
+        assert_eq!(matched.costs.get("a"), Some(&100.0));
+        assert_eq!(matched.costs.get("b"), Some(&200.0));
+        assert_eq!(matched.costs.get("c"), None);
+        assert_eq!(matched.remaining, vec![(1, 50.0)]);
+        assert_eq!(matched.average_remaining_cost(), Some(50.0));
+    }
+
+    #[tokio::test]
+    async fn match_fifo_relisting_test() {
+        let start = Utc::now() - chrono::Duration::days(10);
+        let day = |x: i64| start + chrono::Duration::days(x);
+
+        let purchases = vec![purchase(1, day(0), 1, 100.0), purchase(2, day(2), 1, 200.0)];
+        let consumptions = vec![
+            // Expired on day 3, the item is back and listed again.
+            Consumption {
+                date: day(1),
+                quantity: 1,
+                raffle_id: Some("a".to_string()),
+                returned_at: Some(day(3)),
+            },
+            Consumption {
+                date: day(4),
+                quantity: 1,
+                raffle_id: Some("b".to_string()),
+                returned_at: None,
+            },
+        ];
+
+        let matched = match_fifo(&purchases, &consumptions);
+        assert_eq!(matched.costs.get("a"), Some(&100.0));
+        assert_eq!(matched.costs.get("b"), Some(&100.0));
+        assert_eq!(matched.remaining, vec![(1, 200.0)]);
+
+        // Not listed again yet, the returned item is still held.
+        let matched = match_fifo(&purchases, &consumptions[..1]);
+        assert_eq!(matched.remaining, vec![(1, 100.0), (1, 200.0)]);
+    }
+}
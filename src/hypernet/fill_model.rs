@@ -0,0 +1,296 @@
+use crate::database::hypernet_raffle_model::HypernetRaffleStatus;
+use crate::hypernet::profit::RaffleSetup;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// How far the markup of a past raffle may be off to count as similar.
+const SIMILAR_MARKUP: f64 = 0.1;
+/// Below this many similar raffles with the same ticket count, raffles of all ticket counts are used.
+const MIN_SIMILAR_SAMPLES: usize = 5;
+
+/// Log2 of the ticket count, log10 of the total value, markup over the sell price and the
+/// type's own fill rate.
+const FEATURE_COUNT: usize = 4;
+const TRAINING_ITERATIONS: usize = 5000;
+const LEARNING_RATE: f64 = 0.1;
+const L2_PENALTY: f64 = 0.001;
+
+/// A finished or expired raffle.
+#[derive(Debug, Clone)]
+pub struct FillSample {
+    pub type_id: i32,
+    pub ticket_count: i32,
+    pub ticket_price: f64,
+    pub sell_price: f64,
+    pub finished: bool,
+}
+
+impl FillSample {
+    pub fn markup(&self) -> f64 {
+        self.ticket_count as f64 * self.ticket_price / self.sell_price
+    }
+}
+
+pub async fn load_fill_samples(postgres: &PgPool) -> anyhow::Result<Vec<FillSample>> {
+    let rows = sqlx::query_file!("./sql/hypernet_raffle/select_fill_history.sql")
+        .fetch_all(postgres)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|x| {
+            Some(FillSample {
+                type_id: x.type_id,
+                ticket_count: x.ticket_count,
+                ticket_price: x.ticket_price,
+                sell_price: x.sell_price?,
+                finished: matches!(x.status, HypernetRaffleStatus::Finished),
+            })
+        })
+        .collect())
+}
+
+/// Past raffles and whether they sold out, used to estimate how likely a setup is to finish.
+#[derive(Debug, Clone, Default)]
+pub struct FillHistory {
+    samples: Vec<FillSample>,
+}
+
+impl FillHistory {
+    pub fn new(samples: Vec<FillSample>) -> Self {
+        FillHistory { samples }
+    }
+
+    /// Share of similar past raffles that sold out, with Laplace smoothing.
+    pub fn fill_rate(&self, ticket_count: i32, markup: f64) -> f64 {
+        let similar_markup = |x: &&FillSample| (x.markup() - markup).abs() <= SIMILAR_MARKUP;
+
+        let mut similar: Vec<_> = self
+            .samples
+            .iter()
+            .filter(similar_markup)
+            .filter(|x| x.ticket_count == ticket_count)
+            .collect();
+        if similar.len() < MIN_SIMILAR_SAMPLES {
+            similar = self.samples.iter().filter(similar_markup).collect();
+        }
+
+        let finished = similar.iter().filter(|x| x.finished).count();
+        (finished as f64 + 1.0) / (similar.len() as f64 + 2.0)
+    }
+}
+
+/// Logistic regression predicting whether a raffle sells out, trained on our own raffles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillModel {
+    /// Bias followed by one weight per standardized feature.
+    weights: [f64; FEATURE_COUNT + 1],
+    means: [f64; FEATURE_COUNT],
+    stds: [f64; FEATURE_COUNT],
+    /// Finished and total raffles per type, used for the item type feature.
+    type_counts: HashMap<i32, (u32, u32)>,
+}
+
+impl FillModel {
+    pub fn train(samples: &[FillSample]) -> Self {
+        let mut type_counts: HashMap<i32, (u32, u32)> = HashMap::new();
+        for sample in samples {
+            let counts = type_counts.entry(sample.type_id).or_default();
+            counts.0 += sample.finished as u32;
+            counts.1 += 1;
+        }
+
+        // Leave the sample itself out of its type's fill rate, otherwise the model learns to
+        // trust that feature far more than it should.
+        let features: Vec<[f64; FEATURE_COUNT]> = samples
+            .iter()
+            .map(|x| {
+                let (finished, total) = type_counts[&x.type_id];
+                let type_fill_rate = smoothed_rate(finished - x.finished as u32, total - 1);
+                raw_features(x.ticket_count, x.ticket_price, x.sell_price, type_fill_rate)
+            })
+            .collect();
+
+        let mut means = [0.0; FEATURE_COUNT];
+        let mut stds = [1.0; FEATURE_COUNT];
+        if !features.is_empty() {
+            let n = features.len() as f64;
+            for i in 0..FEATURE_COUNT {
+                means[i] = features.iter().map(|x| x[i]).sum::<f64>() / n;
+                let variance = features
+                    .iter()
+                    .map(|x| (x[i] - means[i]).powi(2))
+                    .sum::<f64>()
+                    / n;
+                stds[i] = if variance > 0.0 { variance.sqrt() } else { 1.0 };
+            }
+        }
+
+        let inputs: Vec<[f64; FEATURE_COUNT + 1]> = features
+            .iter()
+            .map(|x| with_bias(&standardize(x, &means, &stds)))
+            .collect();
+
+        let mut weights = [0.0; FEATURE_COUNT + 1];
+        for _ in 0..TRAINING_ITERATIONS {
+            let mut gradient = [0.0; FEATURE_COUNT + 1];
+            for (input, sample) in inputs.iter().zip(samples) {
+                let error = sigmoid(dot(&weights, input)) - sample.finished as u8 as f64;
+                for i in 0..gradient.len() {
+                    gradient[i] += error * input[i];
+                }
+            }
+            for i in 0..weights.len() {
+                let penalty = if i == 0 { 0.0 } else { L2_PENALTY * weights[i] };
+                weights[i] -= LEARNING_RATE * (gradient[i] / inputs.len().max(1) as f64 + penalty);
+            }
+        }
+
+        FillModel {
+            weights,
+            means,
+            stds,
+            type_counts,
+        }
+    }
+
+    /// Probability that a raffle of this type and setup sells out.
+    pub fn predict(&self, type_id: i32, setup: &RaffleSetup, sell_price: f64) -> f64 {
+        let (finished, total) = self.type_counts.get(&type_id).copied().unwrap_or((0, 0));
+        let features = raw_features(
+            setup.ticket_count,
+            setup.ticket_price,
+            sell_price,
+            smoothed_rate(finished, total),
+        );
+        sigmoid(dot(
+            &self.weights,
+            &with_bias(&standardize(&features, &self.means, &self.stds)),
+        ))
+    }
+}
+
+/// The trained model if there is one, otherwise the raw history of similar raffles.
+pub enum FillEstimator {
+    Model(FillModel),
+    History(FillHistory),
+}
+
+impl FillEstimator {
+    pub async fn load(postgres: &PgPool) -> anyhow::Result<Self> {
+        let model: Option<String> =
+            sqlx::query_file_scalar!("./sql/fill_model/select_latest_model.sql")
+                .fetch_optional(postgres)
+                .await?;
+
+        if let Some(model) = model {
+            match serde_json::from_str(&model) {
+                Ok(model) => return Ok(FillEstimator::Model(model)),
+                Err(e) => warn!("Stored fill model could not be read: {:?}", e),
+            }
+        }
+
+        Ok(FillEstimator::History(FillHistory::new(
+            load_fill_samples(postgres).await?,
+        )))
+    }
+
+    /// Probability that a raffle of this type and setup sells out.
+    pub fn fill_probability(&self, type_id: i32, setup: &RaffleSetup, sell_price: f64) -> f64 {
+        match self {
+            FillEstimator::Model(model) => model.predict(type_id, setup, sell_price),
+            FillEstimator::History(history) => {
+                history.fill_rate(setup.ticket_count, setup.item_value() / sell_price)
+            }
+        }
+    }
+}
+
+fn raw_features(
+    ticket_count: i32,
+    ticket_price: f64,
+    sell_price: f64,
+    type_fill_rate: f64,
+) -> [f64; FEATURE_COUNT] {
+    let item_value = (ticket_count as f64 * ticket_price).max(1.0);
+    [
+        (ticket_count.max(1) as f64).log2(),
+        item_value.log10(),
+        item_value / sell_price,
+        type_fill_rate,
+    ]
+}
+
+fn smoothed_rate(finished: u32, total: u32) -> f64 {
+    (finished as f64 + 1.0) / (total as f64 + 2.0)
+}
+
+fn standardize(
+    features: &[f64; FEATURE_COUNT],
+    means: &[f64; FEATURE_COUNT],
+    stds: &[f64; FEATURE_COUNT],
+) -> [f64; FEATURE_COUNT] {
+    let mut standardized = [0.0; FEATURE_COUNT];
+    for i in 0..FEATURE_COUNT {
+        standardized[i] = (features[i] - means[i]) / stds[i];
+    }
+    standardized
+}
+
+fn with_bias(features: &[f64; FEATURE_COUNT]) -> [f64; FEATURE_COUNT + 1] {
+    let mut input = [1.0; FEATURE_COUNT + 1];
+    input[1..].copy_from_slice(features);
+    input
+}
+
+fn dot(a: &[f64; FEATURE_COUNT + 1], b: &[f64; FEATURE_COUNT + 1]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hypernet::fill_model::{FillModel, FillSample};
+    use crate::hypernet::profit::RaffleSetup;
+
+    #[tokio::test]
+    async fn fill_model_test() {
+        // Raffles priced at the market sell out, overpriced ones expire.
+        let samples: Vec<FillSample> = (0..40)
+            .map(|i| {
+                let markup = 0.9 + (i % 10) as f64 * 0.1;
+                FillSample {
+                    type_id: i % 3,
+                    ticket_count: 16,
+                    ticket_price: markup * 1_000_000.0 / 16.0,
+                    sell_price: 1_000_000.0,
+                    finished: markup < 1.4,
+                }
+            })
+            .collect();
+
+        let model = FillModel::train(&samples);
+        let setup = |markup: f64| RaffleSetup {
+            ticket_count: 16,
+            ticket_price: markup * 1_000_000.0 / 16.0,
+            owned_tickets: None,
+            buy_price: None,
+            hypercore_sell_price: None,
+            plex_price: None,
+        };
+
+        let cheap = model.predict(0, &setup(1.0), 1_000_000.0);
+        let expensive = model.predict(0, &setup(1.8), 1_000_000.0);
+        assert!(cheap > 0.5, "{}", cheap);
+        assert!(expensive < 0.5, "{}", expensive);
+
+        let model: FillModel =
+            serde_json::from_str(&serde_json::to_string(&model).unwrap()).unwrap();
+        assert_eq!(model.predict(0, &setup(1.0), 1_000_000.0), cheap);
+    }
+}
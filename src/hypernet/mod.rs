@@ -0,0 +1,10 @@
+pub mod cost_basis;
+pub mod fill_model;
+pub mod notifications;
+pub mod planner;
+pub mod prices;
+pub mod profit;
+pub mod reconcile;
+pub mod reprocess;
+pub mod results;
+pub mod types;
@@ -0,0 +1,214 @@
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use anyhow::anyhow;
+use chrono::Utc;
+use rfesi::groups::Notification;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub const RAFFLE_CREATED: &str = "RaffleCreated";
+pub const RAFFLE_EXPIRED: &str = "RaffleExpired";
+pub const RAFFLE_FINISHED: &str = "RaffleFinished";
+
+/// Whether the notification type belongs to the Hypernet.
+pub fn is_raffle_notification(notification_type: &str) -> bool {
+    notification_type.starts_with("Raffle")
+}
+
+/// The YAML body shared by `RaffleCreated`, `RaffleExpired` and `RaffleFinished`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RaffleBody {
+    pub location_id: i32,
+    pub owner_id: i32,
+    #[serde(deserialize_with = "deserialize_raffle_id")]
+    pub raffle_id: String,
+    pub ticket_count: i32,
+    pub ticket_price: f64,
+    pub type_id: i32,
+    /// Fields CCP sends that we do not use (yet).
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// Raffle IDs are usually hex strings, but YAML reads an all-digit one as a number.
+fn deserialize_raffle_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    match serde_yaml::Value::deserialize(deserializer)? {
+        serde_yaml::Value::String(s) => Ok(s),
+        serde_yaml::Value::Number(n) => Ok(n.to_string()),
+        other => Err(serde::de::Error::custom(format!(
+            "invalid raffle_id: {:?}",
+            other
+        ))),
+    }
+}
+
+pub struct RaffleNotification {
+    pub notification_id: i64,
+    pub notification_type: String,
+    pub raffle: EvEHypernetRaffle,
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// A notification that could not be parsed. It is reported and skipped, the rest of the batch is
+/// still processed.
+#[derive(Debug)]
+pub struct NotificationParseError {
+    pub notification_id: i64,
+    pub error: anyhow::Error,
+}
+
+impl Display for NotificationParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {:#}", self.notification_id, self.error)
+    }
+}
+
+pub struct ParsedRaffles {
+    pub raffles: Vec<RaffleNotification>,
+    pub errors: Vec<NotificationParseError>,
+}
+
+pub fn parse_raffles(raffles: &[&Notification], char_id: i32) -> ParsedRaffles {
+    let mut parsed = ParsedRaffles {
+        raffles: vec![],
+        errors: vec![],
+    };
+    for raffle in raffles.iter() {
+        let result = chrono::DateTime::from_str(&raffle.timestamp)
+            .map_err(anyhow::Error::from)
+            .and_then(|timestamp| {
+                parse_raffle(
+                    raffle.notification_id,
+                    &raffle.notification_type,
+                    timestamp,
+                    raffle.text.as_deref(),
+                    char_id,
+                )
+            });
+        match result {
+            Ok(raffle) => parsed.raffles.push(raffle),
+            Err(error) => parsed.errors.push(NotificationParseError {
+                notification_id: raffle.notification_id,
+                error,
+            }),
+        }
+    }
+
+    parsed
+}
+
+pub fn parse_raffle(
+    notification_id: i64,
+    notification_type: &str,
+    timestamp: chrono::DateTime<Utc>,
+    text: Option<&str>,
+    char_id: i32,
+) -> Result<RaffleNotification, anyhow::Error> {
+    let text = text.ok_or(anyhow!("Missing text"))?;
+    let body: RaffleBody = serde_yaml::from_str(text)
+        .map_err(|e| anyhow!("Invalid {} body: {}", notification_type, e))?;
+
+    Ok(RaffleNotification {
+        notification_id,
+        notification_type: notification_type.to_string(),
+        raffle: EvEHypernetRaffle {
+            location_id: body.location_id,
+            owner_id: body.owner_id,
+            character_id: char_id,
+            raffle_id: body.raffle_id,
+            ticket_count: body.ticket_count,
+            ticket_price: body.ticket_price,
+            type_id: body.type_id,
+            buy_price: None,
+            sell_price: None,
+            hypercore_sell_price: None,
+            hypercore_buy_price: None,
+            plex_price: None,
+            owned_tickets: None,
+            placeholder: false,
+            status: HypernetRaffleStatus::Created,
+            result: HypernetRaffleResult::None,
+            created_at: timestamp,
+            role: if body.owner_id == char_id {
+                HypernetRaffleRole::Owner
+            } else {
+                HypernetRaffleRole::Participant
+            },
+            isk_spent: None,
+            item_cost_basis: None,
+            hypercore_unit_cost: None,
+        },
+        extra: body.extra,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_fixture(notification_type: &str, text: &str) -> anyhow::Result<RaffleNotification> {
+        parse_raffle(1, notification_type, Utc::now(), Some(text), 2112000000)
+    }
+
+    #[tokio::test]
+    async fn parse_fixture_corpus_test() {
+        let created = parse_fixture(
+            RAFFLE_CREATED,
+            include_str!("../../fixtures/notifications/raffle_created.yaml"),
+        )
+        .unwrap();
+        assert_eq!(created.raffle.raffle_id, "8c4f2e1d9a7b4c3e8f6a5d2b1c0e9f8a");
+        assert_eq!(created.raffle.location_id, 60003760);
+        assert_eq!(created.raffle.owner_id, 2112000000);
+        assert_eq!(created.raffle.ticket_count, 16);
+        assert_eq!(created.raffle.ticket_price, 12500000.0);
+        assert_eq!(created.raffle.type_id, 17738);
+        assert_eq!(created.raffle.role, HypernetRaffleRole::Owner);
+        assert!(created.extra.is_empty());
+
+        let expired = parse_fixture(
+            RAFFLE_EXPIRED,
+            include_str!("../../fixtures/notifications/raffle_expired.yaml"),
+        )
+        .unwrap();
+        assert_eq!(expired.raffle.ticket_count, 32);
+        assert_eq!(expired.raffle.ticket_price, 3000000.0);
+        assert_eq!(expired.raffle.role, HypernetRaffleRole::Participant);
+
+        let finished = parse_fixture(
+            RAFFLE_FINISHED,
+            include_str!("../../fixtures/notifications/raffle_finished.yaml"),
+        )
+        .unwrap();
+        assert_eq!(finished.raffle.type_id, 11567);
+        assert!(finished.extra.is_empty());
+
+        let numeric_id = parse_fixture(
+            RAFFLE_CREATED,
+            include_str!("../../fixtures/notifications/raffle_created_numeric_id.yaml"),
+        )
+        .unwrap();
+        assert_eq!(numeric_id.raffle.raffle_id, "1234567890");
+
+        // Fields CCP may add later are kept instead of failing the notification
+        let extra_fields = parse_fixture(
+            RAFFLE_CREATED,
+            &(include_str!("../../fixtures/notifications/raffle_created.yaml").to_string()
+                + "new_field: 1\n"),
+        )
+        .unwrap();
+        assert_eq!(extra_fields.raffle.ticket_count, 16);
+        assert!(extra_fields.extra.contains_key("new_field"));
+
+        for malformed in [
+            include_str!("../../fixtures/notifications/malformed_missing_field.yaml"),
+            include_str!("../../fixtures/notifications/malformed_bad_number.yaml"),
+            include_str!("../../fixtures/notifications/malformed_not_yaml.yaml"),
+        ] {
+            assert!(parse_fixture(RAFFLE_CREATED, malformed).is_err());
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::hypernet::profit::ProfitType::{Expired, Loser};
+use crate::hypernet::profit::{
+    calculate_profit, expected_value, fill_weighted_expected_value, RaffleSetup,
+};
+
+/// Ticket counts the Hypernet lets you choose from.
+pub const ALLOWED_TICKET_COUNTS: [i32; 7] = [8, 16, 32, 64, 128, 256, 512];
+
+/// Total raffle value relative to the item's sell price that the planner tries.
+const MIN_MARKUP: f64 = 0.8;
+const MAX_MARKUP: f64 = 2.0;
+const MARKUP_STEP: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum PlanGoal {
+    #[name = "Maximize expected value"]
+    MaxExpectedValue,
+    #[name = "Minimize loss"]
+    MinLoss,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlanCandidate {
+    pub setup: RaffleSetup,
+    /// Estimated chance that all tickets are sold.
+    pub fill_probability: f64,
+    /// Expected value if the raffle finishes.
+    pub expected_value: f64,
+    /// Profit if the raffle finishes and we lose.
+    pub loss: f64,
+    /// Expected value including the chance of the raffle expiring.
+    pub adjusted_expected_value: f64,
+    /// Expected loss including the chance of the raffle expiring.
+    pub adjusted_loss: f64,
+}
+
+/// Scores every allowed ticket count and a range of ticket prices for the item and returns the
+/// best `limit` setups for the goal.
+///
+/// `base` provides the prices and the owner's ticket share. `owned_share` is the fraction of tickets
+/// the owner buys; `None` keeps the default assumption of half.
+pub fn plan(
+    base: &RaffleSetup,
+    sell_price: f64,
+    owned_share: Option<f64>,
+    fill_probability: impl Fn(&RaffleSetup) -> f64,
+    goal: PlanGoal,
+    limit: usize,
+) -> Vec<PlanCandidate> {
+    let mut candidates = vec![];
+
+    for ticket_count in ALLOWED_TICKET_COUNTS {
+        let mut markup = MIN_MARKUP;
+        while markup <= MAX_MARKUP + f64::EPSILON {
+            let ticket_price = round_significant(markup * sell_price / ticket_count as f64, 4);
+            markup += MARKUP_STEP;
+            if ticket_price < 1.0 {
+                continue;
+            }
+
+            let setup = RaffleSetup {
+                ticket_count,
+                ticket_price,
+                owned_tickets: owned_share.map(|x| (x * ticket_count as f64).round() as i32),
+                ..base.clone()
+            };
+
+            let fill_probability = fill_probability(&setup).clamp(0.0, 1.0);
+            let (Some(expected_value), Some(adjusted_expected_value), Some(loss), Some(expired)) = (
+                expected_value(&setup),
+                fill_weighted_expected_value(&setup, fill_probability),
+                calculate_profit(&setup, Loser),
+                calculate_profit(&setup, Expired),
+            ) else {
+                continue;
+            };
+
+            candidates.push(PlanCandidate {
+                setup,
+                fill_probability,
+                expected_value,
+                loss,
+                adjusted_expected_value,
+                adjusted_loss: fill_probability * loss + (1.0 - fill_probability) * expired,
+            });
+        }
+    }
+
+    match goal {
+        PlanGoal::MaxExpectedValue => candidates.sort_by(|a, b| {
+            b.adjusted_expected_value
+                .total_cmp(&a.adjusted_expected_value)
+        }),
+        PlanGoal::MinLoss => candidates.sort_by(|a, b| b.adjusted_loss.total_cmp(&a.adjusted_loss)),
+    }
+    candidates.truncate(limit);
+    candidates
+}
+
+fn round_significant(value: f64, digits: i32) -> f64 {
+    if value <= 0.0 {
+        return value;
+    }
+    let magnitude = 10_f64.powi(value.log10().floor() as i32 - digits + 1);
+    (value / magnitude).round() * magnitude
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hypernet::planner::{plan, round_significant, PlanGoal};
+    use crate::hypernet::profit::RaffleSetup;
+
+    #[tokio::test]
+    async fn plan_test() {
+        let base = RaffleSetup {
+            ticket_count: 0,
+            ticket_price: 0.0,
+            owned_tickets: None,
+            buy_price: Some(54730000.0),
+            hypercore_sell_price: Some(327600.0),
+            plex_price: Some(5736785.34),
+        };
+
+        // Overpriced raffles never sell out, so the planner should not recommend them.
+        let fill_probability = |setup: &RaffleSetup| {
+            if setup.item_value() / 58430000.0 > 1.3 {
+                0.0
+            } else {
+                1.0
+            }
+        };
+        let candidates = plan(
+            &base,
+            58430000.0,
+            None,
+            fill_probability,
+            PlanGoal::MaxExpectedValue,
+            5,
+        );
+
+        assert_eq!(candidates.len(), 5);
+        for candidate in candidates.iter() {
+            assert!(candidate.setup.item_value() / 58430000.0 <= 1.3);
+        }
+        assert!(candidates
+            .windows(2)
+            .all(|x| x[0].adjusted_expected_value >= x[1].adjusted_expected_value));
+    }
+
+    #[tokio::test]
+    async fn round_significant_test() {
+        assert_eq!(round_significant(1234567.0, 4), 1235000.0);
+        assert_eq!(round_significant(98.76, 2), 99.0);
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::anyhow;
+use log::warn;
+use rfesi::groups::MarketOrder;
+use rfesi::prelude::Esi;
+use std::collections::HashMap;
+use std::env;
+
+pub const THE_FORGE_REGION_ID: i32 = 10000002;
+pub const JITA_4_4_STATION_ID: i64 = 60003760;
+pub const HYPERCORE_TYPE_ID: i32 = 52568;
+pub const PLEX_TYPE_ID: i32 = 44992;
+
+const MAX_ORDER_FETCH_ATTEMPTS: usize = 5;
+/// ESI returns at most this many orders per page.
+const ORDERS_PER_PAGE: usize = 1000;
+
+/// The station whose orders are used to value items, and the region it is in.
+#[derive(Debug, Clone, Copy)]
+pub struct MarketHub {
+    pub region_id: i32,
+    pub station_id: i64,
+}
+
+impl Default for MarketHub {
+    fn default() -> Self {
+        MarketHub {
+            region_id: THE_FORGE_REGION_ID,
+            station_id: JITA_4_4_STATION_ID,
+        }
+    }
+}
+
+impl MarketHub {
+    /// Reads `MARKET_HUB_REGION_ID` and `MARKET_HUB_STATION_ID`, defaulting to Jita 4-4.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let default = MarketHub::default();
+        Ok(MarketHub {
+            region_id: match env::var("MARKET_HUB_REGION_ID") {
+                Ok(x) => x.parse()?,
+                Err(_) => default.region_id,
+            },
+            station_id: match env::var("MARKET_HUB_STATION_ID") {
+                Ok(x) => x.parse()?,
+                Err(_) => default.station_id,
+            },
+        })
+    }
+}
+
+/// Market prices needed to value a raffle.
+/// Item prices are fetched lazily and cached for the lifetime of this struct.
+pub struct MarketPrices {
+    pub hub: MarketHub,
+    pub hypercore_sell_price: Option<f64>,
+    pub hypercore_buy_price: Option<f64>,
+    pub plex_price: Option<f64>,
+    // Prices for each type_id, (sell, buy)
+    item_prices: HashMap<i32, (Option<f64>, Option<f64>)>,
+}
+
+impl MarketPrices {
+    pub async fn fetch(esi: &Esi, hub: MarketHub) -> anyhow::Result<Self> {
+        let hypernet_core_orders =
+            fetch_region_orders(esi, hub.region_id, HYPERCORE_TYPE_ID).await?;
+
+        let hypercore_sell_price = hypernet_core_orders
+            .iter()
+            .filter(|x| !x.is_buy_order)
+            .map(|x| x.price)
+            .min_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let hypercore_buy_price = hypernet_core_orders
+            .iter()
+            .filter(|x| x.is_buy_order)
+            .map(|x| x.price)
+            .max_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let plex_price = esi
+            .group_market()
+            .get_market_prices()
+            .await?
+            .iter()
+            .find(|x| x.type_id == PLEX_TYPE_ID)
+            .and_then(|x| x.average_price);
+
+        Ok(MarketPrices {
+            hub,
+            hypercore_sell_price,
+            hypercore_buy_price,
+            plex_price,
+            item_prices: HashMap::new(),
+        })
+    }
+
+    /// Best (sell, buy) prices for the given type at the market hub.
+    pub async fn item_prices(
+        &mut self,
+        esi: &Esi,
+        type_id: i32,
+    ) -> anyhow::Result<(Option<f64>, Option<f64>)> {
+        if let Some(prices) = self.item_prices.get(&type_id) {
+            return Ok(*prices);
+        }
+
+        let orders = fetch_region_orders(esi, self.hub.region_id, type_id).await?;
+
+        let sell_price = orders
+            .iter()
+            .filter(|x| !x.is_buy_order)
+            .filter(|x| x.location_id == self.hub.station_id)
+            .map(|x| x.price)
+            .min_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let buy_price = orders
+            .iter()
+            .filter(|x| x.is_buy_order)
+            .filter(|x| x.location_id == self.hub.station_id)
+            .map(|x| x.price)
+            .max_by(|a, b| a.partial_cmp(b).unwrap());
+
+        self.item_prices.insert(type_id, (sell_price, buy_price));
+        Ok((sell_price, buy_price))
+    }
+}
+
+/// All orders for a type in a region, following ESI's pagination.
+async fn fetch_region_orders(
+    esi: &Esi,
+    region_id: i32,
+    type_id: i32,
+) -> anyhow::Result<Vec<MarketOrder>> {
+    let mut orders = vec![];
+    let mut page = 1;
+
+    loop {
+        let page_orders = fetch_region_orders_page(esi, region_id, type_id, page).await?;
+        let page_len = page_orders.len();
+        orders.extend(page_orders);
+
+        if page_len < ORDERS_PER_PAGE {
+            return Ok(orders);
+        }
+        page += 1;
+    }
+}
+
+async fn fetch_region_orders_page(
+    esi: &Esi,
+    region_id: i32,
+    type_id: i32,
+    page: i32,
+) -> anyhow::Result<Vec<MarketOrder>> {
+    for _ in 0..MAX_ORDER_FETCH_ATTEMPTS {
+        let orders = esi
+            .group_market()
+            .get_region_orders(region_id, None, Some(page), Some(type_id))
+            .await;
+
+        match orders {
+            Ok(orders) => return Ok(orders),
+            Err(e) => warn!(
+                "Error fetching orders for type_id: {} (page {}). Retrying... ({:?})",
+                type_id, page, e
+            ),
+        }
+    }
+
+    Err(anyhow!(
+        "Failed to fetch orders for type_id: {} (page {})",
+        type_id,
+        page
+    ))
+}
@@ -0,0 +1,296 @@
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::hypernet::profit::ProfitType::{Expired, Loser, Winner};
+
+/// Share of the ticket sales paid out to the owner. 5% go to tax.
+pub const PAYOUT_RATE: f64 = 0.95;
+
+pub enum ProfitType {
+    Winner,
+    Loser,
+    /// Nobody bought the remaining tickets. We keep the item and get our tickets refunded,
+    /// but the Hypercores are gone.
+    Expired,
+}
+
+/// How the item and the Hypercores of a raffle are valued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfitView {
+    /// At the market prices stored with the raffle.
+    MarkToMarket,
+    /// At what the character paid for them.
+    CostBasis,
+}
+
+/// Everything needed to value a raffle, whether it already exists or is only being planned.
+#[derive(Debug, Clone)]
+pub struct RaffleSetup {
+    pub ticket_count: i32,
+    pub ticket_price: f64,
+    /// Tickets the owner holds. `None` if unknown.
+    pub owned_tickets: Option<i32>,
+    pub buy_price: Option<f64>,
+    pub hypercore_sell_price: Option<f64>,
+    pub plex_price: Option<f64>,
+}
+
+impl From<&EvEHypernetRaffle> for RaffleSetup {
+    fn from(raffle: &EvEHypernetRaffle) -> Self {
+        RaffleSetup {
+            ticket_count: raffle.ticket_count,
+            ticket_price: raffle.ticket_price,
+            owned_tickets: raffle.owned_tickets,
+            buy_price: raffle.buy_price,
+            hypercore_sell_price: raffle.hypercore_sell_price,
+            plex_price: raffle.plex_price,
+        }
+    }
+}
+
+impl RaffleSetup {
+    /// The raffle valued at what the character paid for the item and Hypercores, if known.
+    pub fn at_cost_basis(raffle: &EvEHypernetRaffle) -> Option<RaffleSetup> {
+        Some(RaffleSetup {
+            buy_price: Some(raffle.item_cost_basis?),
+            hypercore_sell_price: Some(raffle.hypercore_unit_cost?),
+            ..RaffleSetup::from(raffle)
+        })
+    }
+
+    pub fn item_value(&self) -> f64 {
+        self.ticket_count as f64 * self.ticket_price
+    }
+
+    pub fn payout(&self) -> f64 {
+        self.item_value() * PAYOUT_RATE
+    }
+
+    /// Number of tickets the owner holds. If we don't know, we assume they bought half of them.
+    pub fn owned_tickets(&self) -> f64 {
+        self.owned_tickets
+            .map(|x| x as f64)
+            .unwrap_or(self.ticket_count as f64 * 0.5)
+    }
+
+    pub fn required_cores(&self) -> Option<f64> {
+        Some((self.item_value() / (2.0 * self.plex_price?)).floor())
+    }
+
+    pub fn hypercore_cost(&self) -> Option<f64> {
+        Some(self.required_cores()? * self.hypercore_sell_price?)
+    }
+
+    /// Lowest ticket price (in 0.01 ISK steps) at which the expected value is not negative.
+    pub fn break_even_ticket_price(&self) -> Option<f64> {
+        let mut setup = self.clone();
+        let mut low = 0.0;
+        let mut high = self.buy_price?.max(1.0);
+
+        // Grow the upper bound until it is profitable, give up if it never is.
+        loop {
+            setup.ticket_price = high;
+            if expected_value(&setup)? >= 0.0 {
+                break;
+            }
+            if high > self.buy_price?.max(1.0) * 1e6 {
+                return None;
+            }
+            high *= 2.0;
+        }
+
+        while high - low > 0.01 {
+            setup.ticket_price = (low + high) / 2.0;
+            if expected_value(&setup)? >= 0.0 {
+                high = setup.ticket_price;
+            } else {
+                low = setup.ticket_price;
+            }
+        }
+
+        Some(high)
+    }
+}
+
+pub fn win_probability(setup: &RaffleSetup) -> f64 {
+    if setup.ticket_count <= 0 {
+        return 0.0;
+    }
+    (setup.owned_tickets() / setup.ticket_count as f64).clamp(0.0, 1.0)
+}
+
+pub fn expected_value(setup: &RaffleSetup) -> Option<f64> {
+    let win_probability = win_probability(setup);
+    let profit_win = calculate_profit(setup, Winner)?;
+    let profit_lose = calculate_profit(setup, Loser)?;
+    Some(profit_win * win_probability + profit_lose * (1.0 - win_probability))
+}
+
+/// Expected value including the chance that the raffle expires instead of selling out.
+pub fn fill_weighted_expected_value(setup: &RaffleSetup, fill_probability: f64) -> Option<f64> {
+    Some(
+        fill_probability * expected_value(setup)?
+            + (1.0 - fill_probability) * calculate_profit(setup, Expired)?,
+    )
+}
+
+pub fn calculate_profit(setup: &RaffleSetup, status: ProfitType) -> Option<f64> {
+    let payout = setup.payout();
+    let hypercore_cost = setup.hypercore_cost()?;
+    let ticket_cost = setup.owned_tickets() * setup.ticket_price;
+
+    // If we win, we get the item back and the payout. But we spend our share of tickets.
+    // If we lose, we get nothing back. But we spend our share of tickets.
+    let profit = match status {
+        Winner => {
+            let total_income = setup.buy_price? + payout;
+            let total_expense = setup.buy_price? + hypercore_cost + ticket_cost;
+            total_income - total_expense
+        }
+        Loser => {
+            let total_expense = setup.buy_price? + hypercore_cost + ticket_cost;
+            payout - total_expense
+        }
+        Expired => -hypercore_cost,
+    };
+
+    Some(profit)
+}
+
+/// Profit of a character that bought tickets in somebody else's raffle. It pays for its tickets
+/// and receives the item if it wins. Tickets of expired raffles are refunded.
+pub fn participant_profit(setup: &RaffleSetup, status: ProfitType) -> Option<f64> {
+    let ticket_cost = setup.owned_tickets? as f64 * setup.ticket_price;
+
+    let profit = match status {
+        Winner => setup.buy_price? - ticket_cost,
+        Loser => -ticket_cost,
+        Expired => 0.0,
+    };
+
+    Some(profit)
+}
+
+pub fn participant_expected_value(setup: &RaffleSetup) -> Option<f64> {
+    let win_probability = win_probability(setup);
+    let profit_win = participant_profit(setup, Winner)?;
+    let profit_lose = participant_profit(setup, Loser)?;
+    Some(profit_win * win_probability + profit_lose * (1.0 - win_probability))
+}
+
+/// Expected value of a stored raffle from the side the character is on.
+pub fn raffle_expected_value(raffle: &EvEHypernetRaffle) -> Option<f64> {
+    let setup = RaffleSetup::from(raffle);
+    match raffle.role {
+        HypernetRaffleRole::Owner => expected_value(&setup),
+        HypernetRaffleRole::Participant => participant_expected_value(&setup),
+    }
+}
+
+/// Profit estimated once the outcome of the raffle is known. Participants pay for their tickets
+/// in ISK, so only the owner's view depends on `view`.
+pub fn estimated_profit(raffle: &EvEHypernetRaffle, view: ProfitView) -> Option<f64> {
+    let setup = match (view, raffle.role) {
+        (ProfitView::CostBasis, HypernetRaffleRole::Owner) => RaffleSetup::at_cost_basis(raffle)?,
+        _ => RaffleSetup::from(raffle),
+    };
+    let profit_type = match (raffle.status, raffle.result) {
+        (HypernetRaffleStatus::Expired, _) => Expired,
+        (HypernetRaffleStatus::Finished, HypernetRaffleResult::Winner) => Winner,
+        (HypernetRaffleStatus::Finished, HypernetRaffleResult::Loser) => Loser,
+        _ => return None,
+    };
+
+    match raffle.role {
+        HypernetRaffleRole::Owner => calculate_profit(&setup, profit_type),
+        HypernetRaffleRole::Participant => participant_profit(&setup, profit_type),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hypernet::profit::ProfitType::{Expired, Loser, Winner};
+    use crate::hypernet::profit::{
+        calculate_profit, expected_value, participant_expected_value, participant_profit,
+        win_probability, RaffleSetup,
+    };
+
+    fn setup() -> RaffleSetup {
+        RaffleSetup {
+            ticket_count: 8,
+            ticket_price: 10307323.0,
+            owned_tickets: None,
+            buy_price: Some(54730000.0),
+            hypercore_sell_price: Some(327600.0),
+            plex_price: Some(5736785.34),
+        }
+    }
+
+    #[tokio::test]
+    async fn calculate_profit_test() {
+        let setup = setup();
+
+        // 7 Hypercores for 82,458,584 ISK of tickets, half of which we hold.
+        assert_eq!(setup.required_cores(), Some(7.0));
+        let payout = 82458584.0 * 0.95;
+        let hypercore_cost = 7.0 * 327600.0;
+        let ticket_cost = 4.0 * 10307323.0;
+
+        let profit = calculate_profit(&setup, Winner).unwrap();
+        assert!((profit - (payout - hypercore_cost - ticket_cost)).abs() < 1e-6);
+        assert!((profit - 34813162.8).abs() < 1e-3);
+
+        let profit = calculate_profit(&setup, Loser).unwrap();
+        assert!((profit - (payout - 54730000.0 - hypercore_cost - ticket_cost)).abs() < 1e-6);
+        assert!((profit + 19916837.2).abs() < 1e-3);
+
+        let profit = calculate_profit(&setup, Expired).unwrap();
+        assert_eq!(profit, -hypercore_cost);
+    }
+
+    #[tokio::test]
+    async fn owned_tickets_test() {
+        let mut setup = setup();
+        assert_eq!(win_probability(&setup), 0.5);
+        let assumed_loss = calculate_profit(&setup, Loser).unwrap();
+
+        // Without any tickets we can never win, and losing costs us nothing but the item.
+        setup.owned_tickets = Some(0);
+        assert_eq!(win_probability(&setup), 0.0);
+        let loss = calculate_profit(&setup, Loser).unwrap();
+        assert!(loss > assumed_loss);
+        assert_eq!(expected_value(&setup).unwrap(), loss);
+
+        setup.owned_tickets = Some(2);
+        assert_eq!(win_probability(&setup), 0.25);
+    }
+
+    #[tokio::test]
+    async fn break_even_ticket_price_test() {
+        let mut setup = setup();
+        let break_even = setup.break_even_ticket_price().unwrap();
+
+        setup.ticket_price = break_even;
+        assert!(expected_value(&setup).unwrap() >= 0.0);
+        setup.ticket_price = break_even - 1.0;
+        assert!(expected_value(&setup).unwrap() < 0.0);
+    }
+
+    #[tokio::test]
+    async fn participant_profit_test() {
+        let mut setup = setup();
+        assert_eq!(participant_profit(&setup, Winner), None);
+
+        setup.owned_tickets = Some(2);
+        let ticket_cost = 2.0 * setup.ticket_price;
+        assert_eq!(
+            participant_profit(&setup, Winner).unwrap(),
+            setup.buy_price.unwrap() - ticket_cost
+        );
+        assert_eq!(participant_profit(&setup, Loser).unwrap(), -ticket_cost);
+        assert_eq!(participant_profit(&setup, Expired).unwrap(), 0.0);
+
+        let expected = 0.25 * setup.buy_price.unwrap() - ticket_cost;
+        assert!((participant_expected_value(&setup).unwrap() - expected).abs() < 1e-6);
+    }
+}
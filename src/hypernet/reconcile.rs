@@ -0,0 +1,378 @@
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::database::raffle_wallet_entry::RaffleWalletEntryKind;
+use crate::esi::wallet_journal::{
+    WalletJournalEntry, HYPERNET_PAYOUT, HYPERNET_TAX, HYPERNET_TICKET_REPAYMENT,
+    HYPERNET_TICKET_SALE,
+};
+use crate::hypernet::profit::{RaffleSetup, PAYOUT_RATE};
+use chrono::{DateTime, Utc};
+
+/// How far a payout, tax or refund may be from the time we saw the raffle end.
+const SETTLEMENT_WINDOW: chrono::Duration = chrono::Duration::days(1);
+/// Relative difference allowed between an expected and a booked amount.
+const AMOUNT_TOLERANCE: f64 = 0.01;
+
+/// The parts of a raffle needed to match wallet journal entries to it.
+#[derive(Debug, Clone)]
+pub struct ReconcileRaffle {
+    pub raffle_id: String,
+    pub role: HypernetRaffleRole,
+    pub status: HypernetRaffleStatus,
+    pub ticket_count: i32,
+    pub ticket_price: f64,
+    pub created_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl ReconcileRaffle {
+    fn pot(&self) -> f64 {
+        self.ticket_count as f64 * self.ticket_price
+    }
+
+    fn is_ticket_multiple(&self, amount: f64) -> bool {
+        let tickets = amount.abs() / self.ticket_price;
+        tickets.round() >= 1.0
+            && tickets.round() <= self.ticket_count as f64
+            && (tickets - tickets.round()).abs() < 0.001
+    }
+
+    fn near_end(&self, date: DateTime<Utc>) -> bool {
+        self.ended_at
+            .is_some_and(|ended_at| (date - ended_at).abs() <= SETTLEMENT_WINDOW)
+    }
+
+    /// Owners buy their share right after creating the raffle. Participants' raffles are only
+    /// seen when they end, so purchases before that count.
+    fn in_purchase_window(&self, date: DateTime<Utc>) -> bool {
+        match self.role {
+            HypernetRaffleRole::Owner => {
+                date >= self.created_at - chrono::Duration::minutes(5)
+                    && self.ended_at.is_none_or(|ended_at| date <= ended_at)
+            }
+            HypernetRaffleRole::Participant => {
+                let ended_at = self.ended_at.unwrap_or(self.created_at);
+                date >= ended_at - chrono::Duration::days(30) && date <= ended_at
+            }
+        }
+    }
+}
+
+impl From<&EvEHypernetRaffle> for ReconcileRaffle {
+    fn from(raffle: &EvEHypernetRaffle) -> Self {
+        ReconcileRaffle {
+            raffle_id: raffle.raffle_id.clone(),
+            role: raffle.role,
+            status: raffle.status,
+            ticket_count: raffle.ticket_count,
+            ticket_price: raffle.ticket_price,
+            created_at: raffle.created_at,
+            ended_at: None,
+        }
+    }
+}
+
+fn close_to(amount: f64, expected: f64) -> bool {
+    expected > 0.0 && ((amount - expected) / expected).abs() <= AMOUNT_TOLERANCE
+}
+
+/// What a Hypernet journal entry is, going by its ref type.
+fn entry_kind(entry: &WalletJournalEntry) -> Option<RaffleWalletEntryKind> {
+    match entry.ref_type.as_str() {
+        HYPERNET_TICKET_SALE => Some(RaffleWalletEntryKind::TicketPurchase),
+        HYPERNET_TICKET_REPAYMENT => Some(RaffleWalletEntryKind::Refund),
+        HYPERNET_PAYOUT => Some(RaffleWalletEntryKind::Payout),
+        HYPERNET_TAX => Some(RaffleWalletEntryKind::Tax),
+        _ => None,
+    }
+}
+
+/// Decides what a journal entry means for the raffle, if it belongs to it at all. The ref type
+/// tells what the entry is. The journal does not reference the raffle, so the entry belongs to
+/// it if it names the raffle, or if its amount and time fit the raffle.
+pub fn classify_entry(
+    raffle: &ReconcileRaffle,
+    entry: &WalletJournalEntry,
+) -> Option<RaffleWalletEntryKind> {
+    let kind = entry_kind(entry)?;
+    let possible = match kind {
+        RaffleWalletEntryKind::TicketPurchase => true,
+        RaffleWalletEntryKind::Refund => raffle.status == HypernetRaffleStatus::Expired,
+        RaffleWalletEntryKind::Payout | RaffleWalletEntryKind::Tax => {
+            raffle.role == HypernetRaffleRole::Owner
+                && raffle.status == HypernetRaffleStatus::Finished
+        }
+    };
+    if !possible {
+        return None;
+    }
+    if entry.names_raffle(&raffle.raffle_id) {
+        return Some(kind);
+    }
+
+    let amount = entry.amount?;
+    let date = entry.date()?;
+    let fits = match kind {
+        RaffleWalletEntryKind::TicketPurchase => {
+            amount < 0.0 && raffle.is_ticket_multiple(amount) && raffle.in_purchase_window(date)
+        }
+        RaffleWalletEntryKind::Refund => {
+            amount > 0.0 && raffle.is_ticket_multiple(amount) && raffle.near_end(date)
+        }
+        RaffleWalletEntryKind::Payout => {
+            raffle.near_end(date)
+                && (close_to(amount, raffle.pot()) || close_to(amount, raffle.pot() * PAYOUT_RATE))
+        }
+        RaffleWalletEntryKind::Tax => {
+            raffle.near_end(date) && close_to(-amount, raffle.pot() * (1.0 - PAYOUT_RATE))
+        }
+    };
+    fits.then_some(kind)
+}
+
+/// Finds the raffle a journal entry belongs to. An entry that names no raffle and fits several
+/// of them is left alone, we can't tell which one it was for.
+pub fn match_entry<'a>(
+    raffles: &'a [ReconcileRaffle],
+    entry: &WalletJournalEntry,
+) -> Option<(&'a ReconcileRaffle, RaffleWalletEntryKind)> {
+    if let Some(raffle) = raffles.iter().find(|x| entry.names_raffle(&x.raffle_id)) {
+        return classify_entry(raffle, entry).map(|kind| (raffle, kind));
+    }
+
+    let mut matches = raffles
+        .iter()
+        .filter_map(|raffle| classify_entry(raffle, entry).map(|kind| (raffle, kind)));
+    let first = matches.next()?;
+    matches.next().is_none().then_some(first)
+}
+
+/// Tickets the character bought in a raffle according to the wallet journal.
+pub struct TicketPurchase {
+    pub tickets: i32,
+    pub isk_spent: f64,
+}
+
+/// Derives how many tickets the character bought in a raffle from the wallet journal. `raffles`
+/// are all raffles the purchases could belong to, including this one, so raffles with the same
+/// ticket price at the same time stay apart.
+pub fn derive_ticket_purchase(
+    raffle: &EvEHypernetRaffle,
+    raffles: &[ReconcileRaffle],
+    journal: &[WalletJournalEntry],
+) -> Option<TicketPurchase> {
+    let purchases: Vec<(i64, f64)> = journal
+        .iter()
+        .filter(|entry| {
+            match_entry(raffles, entry).is_some_and(|(x, kind)| {
+                x.raffle_id == raffle.raffle_id && kind == RaffleWalletEntryKind::TicketPurchase
+            })
+        })
+        .filter_map(|x| x.amount)
+        .map(|x| ((-x / raffle.ticket_price).round() as i64, -x))
+        .filter(|(tickets, _)| *tickets >= 1)
+        .collect();
+
+    let tickets: i64 = purchases.iter().map(|(tickets, _)| tickets).sum();
+    if tickets > 0 {
+        Some(TicketPurchase {
+            tickets: tickets.min(raffle.ticket_count as i64) as i32,
+            isk_spent: purchases.iter().map(|(_, isk)| isk).sum(),
+        })
+    } else {
+        None
+    }
+}
+
+/// Profit from the ISK that actually moved, with the item valued at the stored buy price.
+/// Hypercores are bought outside the raffle, so the owner's are still taken from market prices.
+/// `None` until the outcome of the raffle is known and the ISK it moves has arrived: the owner's
+/// payout of a finished raffle, or the refund of tickets in an expired one. `settled` tells
+/// whether such a payout or refund was matched.
+pub fn realized_profit(
+    raffle: &EvEHypernetRaffle,
+    isk_in: f64,
+    isk_out: f64,
+    settled: bool,
+) -> Option<f64> {
+    let net = isk_in - isk_out;
+    let setup = RaffleSetup::from(raffle);
+
+    let awaits_isk = match (raffle.role, raffle.status) {
+        (HypernetRaffleRole::Owner, HypernetRaffleStatus::Finished) => true,
+        (_, HypernetRaffleStatus::Expired) => isk_out > 0.0,
+        _ => false,
+    };
+    if awaits_isk && !settled {
+        return None;
+    }
+
+    match (raffle.role, raffle.status, raffle.result) {
+        (HypernetRaffleRole::Owner, HypernetRaffleStatus::Expired, _) => {
+            Some(net - setup.hypercore_cost()?)
+        }
+        (
+            HypernetRaffleRole::Owner,
+            HypernetRaffleStatus::Finished,
+            HypernetRaffleResult::Winner,
+        ) => Some(net - setup.hypercore_cost()?),
+        (
+            HypernetRaffleRole::Owner,
+            HypernetRaffleStatus::Finished,
+            HypernetRaffleResult::Loser,
+        ) => Some(net - setup.hypercore_cost()? - raffle.buy_price?),
+        (HypernetRaffleRole::Participant, HypernetRaffleStatus::Expired, _) => Some(net),
+        (
+            HypernetRaffleRole::Participant,
+            HypernetRaffleStatus::Finished,
+            HypernetRaffleResult::Winner,
+        ) => Some(net + raffle.buy_price?),
+        (
+            HypernetRaffleRole::Participant,
+            HypernetRaffleStatus::Finished,
+            HypernetRaffleResult::Loser,
+        ) => Some(net),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::hypernet_raffle_model::{
+        EvEHypernetRaffle, HypernetRaffleRole, HypernetRaffleStatus,
+    };
+    use crate::database::raffle_wallet_entry::RaffleWalletEntryKind;
+    use crate::esi::wallet_journal::WalletJournalEntry;
+    use crate::hypernet::notifications::{parse_raffle, RAFFLE_CREATED};
+    use crate::hypernet::reconcile::{
+        classify_entry, derive_ticket_purchase, match_entry, ReconcileRaffle,
+    };
+    use chrono::{DateTime, Utc};
+
+    fn fixture_raffle(created_at: DateTime<Utc>) -> EvEHypernetRaffle {
+        parse_raffle(
+            1,
+            RAFFLE_CREATED,
+            created_at,
+            Some(include_str!(
+                "../../fixtures/notifications/raffle_created.yaml"
+            )),
+            2112000000,
+        )
+        .unwrap()
+        .raffle
+    }
+
+    fn entry(amount: f64, date: DateTime<Utc>, ref_type: &str) -> WalletJournalEntry {
+        WalletJournalEntry {
+            amount: Some(amount),
+            date: date.to_rfc3339(),
+            description: "HyperNet Relay".to_string(),
+            id: 1,
+            reason: None,
+            ref_type: ref_type.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn classify_entry_test() {
+        let created_at = Utc::now() - chrono::Duration::days(2);
+        let ended_at = Utc::now() - chrono::Duration::hours(1);
+        let mut raffle = ReconcileRaffle::from(&fixture_raffle(created_at));
+        raffle.status = HypernetRaffleStatus::Finished;
+        raffle.ended_at = Some(ended_at);
+
+        // 16 tickets at 12.5M
+        let purchase = entry(-100_000_000.0, created_at, "flux_ticket_sale");
+        assert_eq!(
+            classify_entry(&raffle, &purchase),
+            Some(RaffleWalletEntryKind::TicketPurchase)
+        );
+        let payout = entry(190_000_000.0, ended_at, "flux_payout");
+        assert_eq!(
+            classify_entry(&raffle, &payout),
+            Some(RaffleWalletEntryKind::Payout)
+        );
+        let tax = entry(-10_000_000.0, ended_at, "flux_tax");
+        assert_eq!(
+            classify_entry(&raffle, &tax),
+            Some(RaffleWalletEntryKind::Tax)
+        );
+
+        // Entries of other kinds or sizes are not this raffle's.
+        let market = entry(-100_000_000.0, created_at, "market_transaction");
+        assert_eq!(classify_entry(&raffle, &market), None);
+        let other_payout = entry(57_000_000.0, ended_at, "flux_payout");
+        assert_eq!(classify_entry(&raffle, &other_payout), None);
+
+        // An entry naming the raffle belongs to it, in the UUID form as well.
+        let mut named = entry(-1_000_000.0, Utc::now(), "flux_ticket_sale");
+        named.description = "HyperNet ticket 8C4F2E1D-9A7B-4C3E-8F6A-5D2B1C0E9F8A".to_string();
+        assert_eq!(
+            classify_entry(&raffle, &named),
+            Some(RaffleWalletEntryKind::TicketPurchase)
+        );
+
+        raffle.status = HypernetRaffleStatus::Expired;
+        let refund = entry(25_000_000.0, ended_at, "flux_ticket_repayment");
+        assert_eq!(
+            classify_entry(&raffle, &refund),
+            Some(RaffleWalletEntryKind::Refund)
+        );
+        assert_eq!(classify_entry(&raffle, &payout), None);
+
+        // A purchase that fits two raffles can't be told apart.
+        let mut twin = raffle.clone();
+        twin.raffle_id = "0f1e2d3c4b5a69788796a5b4c3d2e1f0".to_string();
+        raffle.status = HypernetRaffleStatus::Created;
+        raffle.ended_at = None;
+        twin.status = HypernetRaffleStatus::Created;
+        twin.ended_at = None;
+        assert!(match_entry(std::slice::from_ref(&raffle), &purchase).is_some());
+        assert!(match_entry(&[raffle, twin], &purchase).is_none());
+    }
+
+    #[tokio::test]
+    async fn derive_ticket_purchase_test() {
+        let created_at = Utc::now() - chrono::Duration::hours(1);
+        let raffle = fixture_raffle(created_at);
+        assert_eq!(raffle.role, HypernetRaffleRole::Owner);
+        let mut other = ReconcileRaffle::from(&raffle);
+        other.raffle_id = "0f1e2d3c4b5a69788796a5b4c3d2e1f0".to_string();
+        other.ticket_price = 3_000_000.0;
+        let raffles = [ReconcileRaffle::from(&raffle), other];
+
+        let journal = [
+            entry(
+                -50_000_000.0,
+                created_at + chrono::Duration::minutes(1),
+                "flux_ticket_sale",
+            ),
+            entry(
+                -25_000_000.0,
+                created_at + chrono::Duration::minutes(2),
+                "flux_ticket_sale",
+            ),
+            // Bought in the other raffle
+            entry(
+                -9_000_000.0,
+                created_at + chrono::Duration::minutes(3),
+                "flux_ticket_sale",
+            ),
+            // Bought before the raffle existed
+            entry(
+                -12_500_000.0,
+                created_at - chrono::Duration::days(1),
+                "flux_ticket_sale",
+            ),
+            entry(-12_500_000.0, created_at, "market_transaction"),
+        ];
+
+        let purchase = derive_ticket_purchase(&raffle, &raffles, &journal).unwrap();
+        assert_eq!(purchase.tickets, 6);
+        assert_eq!(purchase.isk_spent, 75_000_000.0);
+
+        assert!(derive_ticket_purchase(&raffle, &raffles, &journal[2..]).is_none());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::database::raffle_event::RaffleEventKind;
+use crate::database::raw_notification::RawNotification;
+use crate::hypernet::notifications::{
+    parse_raffle, RAFFLE_CREATED, RAFFLE_EXPIRED, RAFFLE_FINISHED,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{query_file, query_file_as, PgPool};
+use std::collections::HashMap;
+
+/// What rebuilding the raffles from the archive changed, or would change in a dry run.
+#[derive(Debug, Default)]
+pub struct ReprocessReport {
+    pub notifications: usize,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub transitioned: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+enum Change {
+    Insert(EvEHypernetRaffle),
+    Update(EvEHypernetRaffle),
+    Transition {
+        raffle_id: String,
+        character_id: i32,
+        from: HypernetRaffleStatus,
+        to: HypernetRaffleStatus,
+        notification_id: i64,
+        occurred_at: DateTime<Utc>,
+    },
+}
+
+/// Runs every archived raffle notification through the current parser and brings the stored
+/// raffles in line with it. Prices, results and ticket shares are left alone and no Discord
+/// messages are sent. Nothing is written unless `apply` is set.
+pub async fn reprocess_raffles(postgres: &PgPool, apply: bool) -> anyhow::Result<ReprocessReport> {
+    let archived: Vec<RawNotification> = query_file_as!(
+        RawNotification,
+        "./sql/raw_notifications/select_all_raw_notifications.sql"
+    )
+    .fetch_all(postgres)
+    .await?;
+
+    let mut report = ReprocessReport {
+        notifications: archived.len(),
+        ..Default::default()
+    };
+    // The raffles as they will look after all changes so far are applied, by raffle and character.
+    let mut raffles: HashMap<(String, i32), EvEHypernetRaffle> = HashMap::new();
+    let mut changes = vec![];
+
+    for notification in archived {
+        let parsed = match parse_raffle(
+            notification.notification_id,
+            &notification.notification_type,
+            notification.timestamp,
+            notification.text.as_deref(),
+            notification.character_id,
+        ) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                report
+                    .errors
+                    .push(format!("{}: {:#}", notification.notification_id, e));
+                continue;
+            }
+        };
+
+        let raffle_id = parsed.raffle.raffle_id.clone();
+        let key = (raffle_id.clone(), notification.character_id);
+        if !raffles.contains_key(&key) {
+            let stored = query_file_as!(
+                EvEHypernetRaffle,
+                "./sql/hypernet_raffle/select_raffle_by_id.sql",
+                raffle_id,
+                notification.character_id
+            )
+            .fetch_optional(postgres)
+            .await?;
+            if let Some(stored) = stored {
+                raffles.insert(key.clone(), stored);
+            }
+        }
+
+        let next_status = match parsed.notification_type.as_str() {
+            RAFFLE_CREATED => {
+                match raffles.get_mut(&key) {
+                    None => {
+                        raffles.insert(key, parsed.raffle.clone());
+                        changes.push(Change::Insert(parsed.raffle));
+                        report.created.push(raffle_id);
+                    }
+                    Some(stored) => {
+                        let parsed = parsed.raffle;
+                        if stored.placeholder
+                            || stored.location_id != parsed.location_id
+                            || stored.owner_id != parsed.owner_id
+                            || stored.ticket_count != parsed.ticket_count
+                            || stored.ticket_price != parsed.ticket_price
+                            || stored.type_id != parsed.type_id
+                        {
+                            stored.location_id = parsed.location_id;
+                            stored.owner_id = parsed.owner_id;
+                            stored.ticket_count = parsed.ticket_count;
+                            stored.ticket_price = parsed.ticket_price;
+                            stored.type_id = parsed.type_id;
+                            if stored.placeholder {
+                                stored.created_at = parsed.created_at;
+                                stored.placeholder = false;
+                            }
+                            changes.push(Change::Update(stored.clone()));
+                            report.updated.push(raffle_id);
+                        }
+                    }
+                }
+                continue;
+            }
+            RAFFLE_EXPIRED => HypernetRaffleStatus::Expired,
+            RAFFLE_FINISHED => HypernetRaffleStatus::Finished,
+            _ => continue,
+        };
+
+        let stored = match raffles.get_mut(&key) {
+            Some(stored) => stored,
+            None => {
+                let placeholder = EvEHypernetRaffle {
+                    placeholder: true,
+                    ..parsed.raffle.clone()
+                };
+                changes.push(Change::Insert(placeholder.clone()));
+                report.created.push(raffle_id.clone());
+                raffles.entry(key).or_insert(placeholder)
+            }
+        };
+
+        if stored.status.can_transition_to(next_status) {
+            changes.push(Change::Transition {
+                raffle_id: raffle_id.clone(),
+                character_id: notification.character_id,
+                from: stored.status,
+                to: next_status,
+                notification_id: parsed.notification_id,
+                occurred_at: notification.timestamp,
+            });
+            stored.status = next_status;
+            report.transitioned.push(raffle_id);
+        }
+    }
+
+    if !apply {
+        return Ok(report);
+    }
+
+    let mut transaction = postgres.begin().await?;
+    for change in changes {
+        match change {
+            Change::Insert(raffle) => {
+                query_file!(
+                    "./sql/hypernet_raffle/insert_raffle.sql",
+                    raffle.location_id,
+                    raffle.owner_id,
+                    raffle.character_id,
+                    raffle.raffle_id,
+                    raffle.ticket_count,
+                    raffle.ticket_price,
+                    raffle.type_id,
+                    HypernetRaffleStatus::Created as HypernetRaffleStatus,
+                    raffle.result as HypernetRaffleResult,
+                    raffle.created_at,
+                    raffle.sell_price,
+                    raffle.buy_price,
+                    raffle.hypercore_buy_price,
+                    raffle.hypercore_sell_price,
+                    raffle.plex_price,
+                    raffle.owned_tickets,
+                    raffle.placeholder,
+                    raffle.role as HypernetRaffleRole,
+                    raffle.isk_spent
+                )
+                .execute(&mut *transaction)
+                .await?;
+                query_file!(
+                    "./sql/raffle_events/insert_event.sql",
+                    raffle.raffle_id,
+                    raffle.character_id,
+                    RaffleEventKind::Created as RaffleEventKind,
+                    Some(HypernetRaffleStatus::Created) as Option<HypernetRaffleStatus>,
+                    None as Option<HypernetRaffleResult>,
+                    None as Option<i64>,
+                    None as Option<i64>,
+                    Some(
+                        json!({ "placeholder": raffle.placeholder, "reprocessed": true })
+                            .to_string()
+                    ),
+                    raffle.created_at,
+                )
+                .execute(&mut *transaction)
+                .await?;
+            }
+            Change::Update(raffle) => {
+                query_file!(
+                    "./sql/hypernet_raffle/update_parsed_fields.sql",
+                    raffle.raffle_id,
+                    raffle.character_id,
+                    raffle.location_id,
+                    raffle.owner_id,
+                    raffle.ticket_count,
+                    raffle.ticket_price,
+                    raffle.type_id,
+                    raffle.created_at,
+                    raffle.placeholder
+                )
+                .execute(&mut *transaction)
+                .await?;
+                query_file!(
+                    "./sql/raffle_events/insert_event.sql",
+                    raffle.raffle_id,
+                    raffle.character_id,
+                    RaffleEventKind::Correction as RaffleEventKind,
+                    None as Option<HypernetRaffleStatus>,
+                    None as Option<HypernetRaffleResult>,
+                    None as Option<i64>,
+                    None as Option<i64>,
+                    Some(
+                        json!({
+                            "location_id": raffle.location_id,
+                            "owner_id": raffle.owner_id,
+                            "ticket_count": raffle.ticket_count,
+                            "ticket_price": raffle.ticket_price,
+                            "type_id": raffle.type_id,
+                            "reprocessed": true,
+                        })
+                        .to_string()
+                    ),
+                    Utc::now(),
+                )
+                .execute(&mut *transaction)
+                .await?;
+            }
+            Change::Transition {
+                raffle_id,
+                character_id,
+                from,
+                to,
+                notification_id,
+                occurred_at,
+            } => {
+                query_file!(
+                    "./sql/hypernet_raffle/transition_status.sql",
+                    raffle_id,
+                    character_id,
+                    from as HypernetRaffleStatus,
+                    to as HypernetRaffleStatus,
+                )
+                .execute(&mut *transaction)
+                .await?;
+                query_file!(
+                    "./sql/raffle_events/insert_event.sql",
+                    raffle_id,
+                    character_id,
+                    RaffleEventKind::StatusChanged as RaffleEventKind,
+                    Some(to) as Option<HypernetRaffleStatus>,
+                    None as Option<HypernetRaffleResult>,
+                    Some(notification_id),
+                    None as Option<i64>,
+                    Some(json!({ "reprocessed": true }).to_string()),
+                    occurred_at,
+                )
+                .execute(&mut *transaction)
+                .await?;
+            }
+        }
+    }
+    transaction.commit().await?;
+
+    Ok(report)
+}
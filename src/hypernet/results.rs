@@ -0,0 +1,63 @@
+use crate::database::hypernet_raffle_model::HypernetRaffleResult;
+use chrono::{DateTime, Utc};
+use rfesi::groups::Asset;
+
+/// How long after a raffle finished we wait for the item to show up before calling it a loss.
+/// ESI caches assets for up to an hour.
+pub const RESULT_GRACE_PERIOD: chrono::Duration = chrono::Duration::hours(2);
+
+/// Quantity of a type the character holds directly in the hangar of a location.
+pub fn held_quantity(assets: &[Asset], type_id: i32, location_id: i32) -> i32 {
+    assets
+        .iter()
+        .filter(|x| x.type_id == type_id && x.location_id == location_id as i64)
+        .map(|x| x.quantity)
+        .sum()
+}
+
+/// Infers the result of a finished raffle from the character's assets.
+///
+/// The winner receives the item at the raffle location. `asset_baseline` is what the character
+/// held there right after the item went into the raffle, and only counts while nothing of the type
+/// was traded since, so any increase is a win. Without a baseline we can't tell a returning item
+/// from one the character already owned and nothing is inferred. Without the item the raffle is
+/// called lost after [`RESULT_GRACE_PERIOD`]. The owner's payout is no sign of a loss, it is paid
+/// whoever wins, and shows up in the journal before a won item shows up in the cached assets.
+pub fn infer_result(
+    asset_baseline: Option<i32>,
+    held: i32,
+    finished_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<HypernetRaffleResult> {
+    let baseline = asset_baseline?;
+    if held > baseline {
+        Some(HypernetRaffleResult::Winner)
+    } else if now - finished_at >= RESULT_GRACE_PERIOD {
+        Some(HypernetRaffleResult::Loser)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::hypernet_raffle_model::HypernetRaffleResult::{Loser, Winner};
+    use crate::hypernet::results::{infer_result, RESULT_GRACE_PERIOD};
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn infer_result_test() {
+        let now = Utc::now();
+        let just_finished = now - chrono::Duration::minutes(10);
+        let long_finished = now - RESULT_GRACE_PERIOD;
+
+        // Compared against what was held right after the item went into the raffle.
+        assert_eq!(infer_result(Some(1), 2, just_finished, now), Some(Winner));
+        assert_eq!(infer_result(Some(1), 1, just_finished, now), None);
+        assert_eq!(infer_result(Some(1), 1, long_finished, now), Some(Loser));
+
+        // Without a baseline a copy at the location proves nothing, and neither does its absence.
+        assert_eq!(infer_result(None, 1, long_finished, now), None);
+        assert_eq!(infer_result(None, 0, long_finished, now), None);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::esi::universe::{
+    get_all_type_ids, get_inventory_type_ids, get_inventory_type_names, NAMES_BATCH_SIZE,
+};
+use rfesi::prelude::Esi;
+use sqlx::PgPool;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub struct EvEType {
+    pub type_id: i32,
+    pub name: String,
+}
+
+/// Name of the given type. Names are cached in the database, so ESI is only asked once per type.
+pub async fn type_name(postgres: &PgPool, esi: &Esi, type_id: i32) -> anyhow::Result<String> {
+    let name: Option<String> =
+        sqlx::query_file_scalar!("./sql/eve_types/select_type_name.sql", type_id)
+            .fetch_optional(postgres)
+            .await?;
+
+    if let Some(name) = name {
+        return Ok(name);
+    }
+
+    let item = esi.group_universe().get_type(type_id).await?;
+    sqlx::query_file!("./sql/eve_types/upsert_type.sql", type_id, item.name)
+        .execute(postgres)
+        .await?;
+
+    Ok(item.name)
+}
+
+/// Resolves user input to a type. Accepts a type id (as sent by the autocomplete) or an exact name.
+pub async fn resolve_type(
+    postgres: &PgPool,
+    esi: &Esi,
+    input: &str,
+) -> anyhow::Result<Option<EvEType>> {
+    let input = input.trim();
+    if let Ok(type_id) = input.parse::<i32>() {
+        let name = type_name(postgres, esi, type_id).await?;
+        return Ok(Some(EvEType { type_id, name }));
+    }
+
+    let known = sqlx::query_file_as!(EvEType, "./sql/eve_types/select_type_by_name.sql", input)
+        .fetch_optional(postgres)
+        .await?;
+    if known.is_some() {
+        return Ok(known);
+    }
+
+    let Some(found) = get_inventory_type_ids(esi, &[input])
+        .await?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+    sqlx::query_file!("./sql/eve_types/upsert_type.sql", found.id, found.name)
+        .execute(postgres)
+        .await?;
+
+    Ok(Some(EvEType {
+        type_id: found.id,
+        name: found.name,
+    }))
+}
+
+/// Known types whose name contains `partial`, for slash command autocompletion. When nothing
+/// matches, ESI is asked whether `partial` is the exact name of a type we have not seen yet.
+pub async fn search_types(
+    postgres: &PgPool,
+    esi: &Esi,
+    partial: &str,
+) -> anyhow::Result<Vec<EvEType>> {
+    let found = sqlx::query_file_as!(
+        EvEType,
+        "./sql/eve_types/search_types.sql",
+        escape_like(partial)
+    )
+    .fetch_all(postgres)
+    .await?;
+    if !found.is_empty() || partial.trim().is_empty() {
+        return Ok(found);
+    }
+
+    Ok(resolve_type(postgres, esi, partial)
+        .await?
+        .into_iter()
+        .collect())
+}
+
+/// Stores the names of all types ESI knows and we do not, so autocompletion works from the start.
+/// Returns how many types were added.
+pub async fn seed_types(postgres: &PgPool, esi: &Esi) -> anyhow::Result<usize> {
+    let known: HashSet<i32> = sqlx::query_file_scalar!("./sql/eve_types/select_type_ids.sql")
+        .fetch_all(postgres)
+        .await?
+        .into_iter()
+        .collect();
+    let missing: Vec<i32> = get_all_type_ids(esi)
+        .await?
+        .into_iter()
+        .filter(|x| !known.contains(x))
+        .collect();
+
+    let mut seeded = 0;
+    for batch in missing.chunks(NAMES_BATCH_SIZE) {
+        let (type_ids, names): (Vec<i32>, Vec<String>) = get_inventory_type_names(esi, batch)
+            .await?
+            .into_iter()
+            .map(|x| (x.id, x.name))
+            .unzip();
+        sqlx::query_file!("./sql/eve_types/upsert_types.sql", &type_ids, &names)
+            .execute(postgres)
+            .await?;
+        seeded += type_ids.len();
+    }
+
+    Ok(seeded)
+}
+
+/// Escapes the ILIKE wildcards so user input only ever matches literally.
+fn escape_like(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hypernet::types::escape_like;
+
+    #[tokio::test]
+    async fn escape_like_test() {
+        assert_eq!(escape_like("Plex"), "Plex");
+        assert_eq!(escape_like("100%_off\\"), "100\\%\\_off\\\\");
+    }
+}
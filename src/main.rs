@@ -2,14 +2,29 @@ mod commands;
 mod context;
 mod cron;
 mod database;
+mod esi;
 mod handler;
+mod hypernet;
+mod notify;
 mod rest;
 
 use crate::commands::change_notification_channel::change_notification_channel;
+use crate::commands::evaluate::evaluate;
 use crate::commands::help::help;
+use crate::commands::notification_backends::notification_backends;
+use crate::commands::notification_digest::notification_digest;
+use crate::commands::notification_settings::notification_settings;
+use crate::commands::plan::plan;
+use crate::commands::raffle::raffle;
+use crate::commands::reprocess_raffles::reprocess_raffles;
+use crate::commands::set_tickets::set_tickets;
+use crate::commands::stats::stats;
+use crate::commands::watchlist::watchlist;
+use crate::commands::webhook::{server_webhook, webhook};
 use crate::context::{AppContext, CronAppContext};
 use crate::cron::start_cron;
 use crate::handler::event_handler;
+use crate::hypernet::prices::MarketHub;
 use actix_web::{web, App, HttpServer};
 use commands::auth::auth;
 use commands::register::register;
@@ -31,6 +46,13 @@ async fn main() -> Result<(), anyhow::Error> {
     let database = PgPool::connect(&env::var("DATABASE_URL")?).await?;
     PgTypeInfo::with_name("hypernet_raffle_status");
     PgTypeInfo::with_name("hypernet_raffle_result");
+    PgTypeInfo::with_name("processed_notification_state");
+    PgTypeInfo::with_name("raffle_event_kind");
+    PgTypeInfo::with_name("hypernet_raffle_role");
+    PgTypeInfo::with_name("raffle_wallet_entry_kind");
+    PgTypeInfo::with_name("outbox_state");
+    PgTypeInfo::with_name("notification_event_kind");
+    PgTypeInfo::with_name("notification_backend");
 
     let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
     let debug = env::var("DEBUG").is_ok();
@@ -109,20 +131,40 @@ async fn main() -> Result<(), anyhow::Error> {
 
     let intents = GatewayIntents::non_privileged();
 
+    let owners = HashSet::from_iter([UserId::new(262702226693160970)]);
+
     let options = poise::FrameworkOptions {
-        commands: vec![help(), auth(), register(), change_notification_channel()],
+        commands: vec![
+            help(),
+            auth(),
+            register(),
+            change_notification_channel(),
+            notification_settings(),
+            notification_backends(),
+            notification_digest(),
+            webhook(),
+            server_webhook(),
+            set_tickets(),
+            raffle(),
+            stats(),
+            evaluate(),
+            plan(),
+            watchlist(),
+            reprocess_raffles(),
+        ],
         allowed_mentions: None,
         initialize_owners: true,
         event_handler: |ctx, event, framework, data| {
             Box::pin(event_handler(ctx, event, framework, data))
         },
-        owners: HashSet::from_iter([UserId::new(262702226693160970)]),
+        owners: owners.clone(),
         ..Default::default()
     };
 
     let data = AppContext {
         esi,
         postgres: database,
+        market_hub: MarketHub::from_env()?,
     };
 
     let data_cloned = data.clone();
@@ -160,6 +202,7 @@ async fn main() -> Result<(), anyhow::Error> {
         App::new()
             .app_data(web::Data::new(http_context.clone()))
             .service(rest::callback::callback)
+            .service(rest::webhook_test::webhook_test)
     })
     .bind(("0.0.0.0", 3000))?;
 
@@ -167,6 +210,8 @@ async fn main() -> Result<(), anyhow::Error> {
         esi: data.esi.clone(),
         postgres: data.postgres.clone(),
         discord_http: client.http.clone(),
+        market_hub: data.market_hub,
+        owners: owners.into_iter().collect(),
     };
 
     tokio::select! {
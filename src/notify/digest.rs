@@ -0,0 +1,174 @@
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::database::notification_digest::DigestEntry;
+use crate::database::notification_preference::NotificationEventKind;
+use crate::hypernet::profit::raffle_expected_value;
+use crate::hypernet::types::type_name;
+use rfesi::prelude::Esi;
+use serenity::all::{
+    ButtonStyle, Colour, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use sqlx::{query_file_as, PgPool};
+use thousands::Separable;
+
+/// Table rows per page. Every row can add up to two options to the result menu, which holds 25.
+const PAGE_SIZE: usize = 10;
+/// Item names are cut to this to keep the table in one line per row.
+const ITEM_WIDTH: usize = 22;
+
+/// Everything bundled into a digest, oldest first.
+pub async fn digest_entries(postgres: &PgPool, digest_id: i64) -> sqlx::Result<Vec<DigestEntry>> {
+    query_file_as!(
+        DigestEntry,
+        "./sql/notification_digest/select_entries.sql",
+        digest_id
+    )
+    .fetch_all(postgres)
+    .await
+}
+
+/// The digest message showing the first page.
+pub async fn digest_message(
+    postgres: &PgPool,
+    esi: &Esi,
+    digest_id: i64,
+    entries: &[DigestEntry],
+) -> anyhow::Result<CreateMessage> {
+    let (embed, components) = render_page(postgres, esi, digest_id, entries, 0).await?;
+    Ok(CreateMessage::new().embed(embed).components(components))
+}
+
+/// One page of the digest: a table of the raffles with their current outcome and expected
+/// value, a menu to set or correct the result of finished raffles and buttons to turn the page.
+pub async fn render_page(
+    postgres: &PgPool,
+    esi: &Esi,
+    digest_id: i64,
+    entries: &[DigestEntry],
+    page: usize,
+) -> anyhow::Result<(CreateEmbed, Vec<CreateActionRow>)> {
+    let pages = entries.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+
+    let mut rows = vec![format!(
+        "{:<width$} {:<8} {:<9} {:>15}",
+        "Item",
+        "Event",
+        "Outcome",
+        "EV",
+        width = ITEM_WIDTH
+    )];
+    let mut options = vec![];
+    let mut finished = vec![];
+    for entry in entries.iter().skip(page * PAGE_SIZE).take(PAGE_SIZE) {
+        let raffle: EvEHypernetRaffle = query_file_as!(
+            EvEHypernetRaffle,
+            "./sql/hypernet_raffle/select_raffle_by_id.sql",
+            entry.raffle_id,
+            entry.character_id
+        )
+        .fetch_one(postgres)
+        .await?;
+        let item_name = type_name(postgres, esi, raffle.type_id).await?;
+
+        rows.push(format!(
+            "{:<width$} {:<8} {:<9} {:>15}",
+            item_name.chars().take(ITEM_WIDTH).collect::<String>(),
+            event_label(entry.event_kind),
+            outcome(&raffle),
+            raffle_expected_value(&raffle)
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            width = ITEM_WIDTH
+        ));
+
+        // A raffle shows up once per event, but gets its options only once. A picked result can
+        // be changed to the other one.
+        if raffle.status == HypernetRaffleStatus::Finished && !finished.contains(&raffle.key()) {
+            let choices = [
+                (HypernetRaffleResult::Winner, "won", "Won"),
+                (HypernetRaffleResult::Loser, "lost", "Lost"),
+            ];
+            for (result, action, label) in choices {
+                if raffle.result == result {
+                    continue;
+                }
+                let label = match raffle.result {
+                    HypernetRaffleResult::None => format!("{}: {}", label, item_name),
+                    _ => format!("Change to {}: {}", label, item_name),
+                };
+                options.push(
+                    CreateSelectMenuOption::new(label, format!("{}:{}", action, raffle.key()))
+                        .description(&entry.character_name),
+                );
+            }
+            finished.push(raffle.key());
+        }
+    }
+
+    let embed = CreateEmbed::new()
+        .title("Hypernet Raffle Digest")
+        .description(format!("```\n{}\n```", rows.join("\n")))
+        .color(Colour::from((255, 255, 255)))
+        .footer(CreateEmbedFooter::new(format!(
+            "Page {}/{} · {} events · Digest {}",
+            page + 1,
+            pages,
+            entries.len(),
+            digest_id
+        )));
+
+    let mut components = vec![];
+    if !options.is_empty() {
+        components.push(CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                format!("digest-result:{}:{}", digest_id, page),
+                CreateSelectMenuKind::String { options },
+            )
+            .placeholder("Set or change the result of a finished raffle"),
+        ));
+    }
+    if pages > 1 {
+        components.push(CreateActionRow::Buttons(vec![
+            CreateButton::new(format!(
+                "digest-page:{}:{}",
+                digest_id,
+                page.saturating_sub(1)
+            ))
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+            CreateButton::new(format!("digest-page:{}:{}", digest_id, page + 1))
+                .label("Next")
+                .style(ButtonStyle::Secondary)
+                .disabled(page + 1 == pages),
+        ]));
+    }
+
+    Ok((embed, components))
+}
+
+fn event_label(kind: NotificationEventKind) -> &'static str {
+    match kind {
+        NotificationEventKind::Created => "Created",
+        NotificationEventKind::Expired => "Expired",
+        NotificationEventKind::Finished => "Finished",
+        NotificationEventKind::ResultSet => "Result",
+        NotificationEventKind::ResultReminder => "Reminder",
+        NotificationEventKind::LowHypercores => "Cores",
+    }
+}
+
+/// Where the raffle stands now, which may be past the event that put it into the digest.
+fn outcome(raffle: &EvEHypernetRaffle) -> &'static str {
+    match (raffle.status, raffle.result, raffle.role) {
+        (_, HypernetRaffleResult::Winner, _) => "Won",
+        (_, HypernetRaffleResult::Loser, _) => "Lost",
+        (HypernetRaffleStatus::Created, _, HypernetRaffleRole::Owner) => "Selling",
+        (HypernetRaffleStatus::Created, _, HypernetRaffleRole::Participant) => "Running",
+        (HypernetRaffleStatus::Expired, _, _) => "Expired",
+        (HypernetRaffleStatus::Finished, _, _) => "Pending",
+    }
+}
@@ -0,0 +1,339 @@
+use crate::context::CronAppContext;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::hypernet_raffle_model::HypernetRaffleResult;
+use crate::database::notification_preference::NotificationEventKind;
+use crate::notify::{
+    notification_target, DeliveryId, NotificationEvent, NotificationTarget, Notifier, NotifyError,
+};
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::json;
+use serenity::all::{
+    ButtonStyle, ChannelId, Color, CreateActionRow, CreateButton, CreateEmbed, CreateMessage,
+    EditMessage, Embed, HttpError, Message, MessageId, UserId,
+};
+use sqlx::{query_file, query_file_scalar};
+
+/// Posts into the channel picked for the event, the configured notification channel, or a DM
+/// without either. Channels we can't post into anymore are disabled so the user gets the
+/// next one in line instead.
+pub struct DiscordChannelNotifier {
+    /// The channel picked for this kind of event, if any.
+    pub channel_id: Option<i64>,
+}
+
+#[async_trait]
+impl Notifier for DiscordChannelNotifier {
+    async fn notify(
+        &self,
+        ctx: &CronAppContext,
+        character: &EvECharacterInfo,
+        event: &NotificationEvent,
+        delivery_id: DeliveryId,
+    ) -> Result<(), NotifyError> {
+        let target = match self.channel_id {
+            Some(channel_id) => NotificationTarget::Channel(ChannelId::new(channel_id as u64)),
+            None => notification_target(&ctx.postgres, character)
+                .await
+                .map_err(NotifyError::Transient)?,
+        };
+        let channel_id = target.channel_id(&ctx.discord_http).await?;
+
+        let error = match send_live(ctx, channel_id, event, delivery_id).await {
+            Ok(()) => return Ok(()),
+            Err(NotifyError::Permanent(error)) => error,
+            Err(error) => return Err(error),
+        };
+        if !matches!(target, NotificationTarget::Channel(_)) {
+            return Err(NotifyError::Permanent(error));
+        }
+
+        let reason = error.to_string();
+        if self.channel_id.is_some() {
+            disable_event_channel(ctx, channel_id)
+                .await
+                .map_err(|e| NotifyError::Transient(e.into()))?;
+            info!("Removed event channel {}: {}", channel_id, reason);
+            tell_user(ctx, character.discord_user_id, channel_id, &reason).await;
+            return Err(NotifyError::Redirected(error));
+        }
+
+        let disabled: Option<i64> = query_file_scalar!(
+            "./sql/notification_channel/disable_channel.sql",
+            channel_id.get() as i64,
+            reason
+        )
+        .fetch_optional(&ctx.postgres)
+        .await
+        .map_err(|e| NotifyError::Transient(e.into()))?;
+
+        if let Some(discord_user_id) = disabled {
+            info!(
+                "Disabled notification channel {} of user {}: {}",
+                channel_id, discord_user_id, reason
+            );
+            tell_user(ctx, discord_user_id, channel_id, &reason).await;
+        }
+
+        Err(NotifyError::Redirected(error))
+    }
+}
+
+/// Drops a channel from the event preferences and from the messages still queued for it.
+async fn disable_event_channel(ctx: &CronAppContext, channel_id: ChannelId) -> sqlx::Result<()> {
+    let mut transaction = ctx.postgres.begin().await?;
+    query_file!(
+        "./sql/notification_preference/clear_channel.sql",
+        channel_id.get() as i64
+    )
+    .execute(&mut *transaction)
+    .await?;
+    query_file!(
+        "./sql/notification_outbox/clear_channel.sql",
+        channel_id.get() as i64
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await
+}
+
+async fn tell_user(
+    ctx: &CronAppContext,
+    discord_user_id: i64,
+    channel_id: ChannelId,
+    reason: &str,
+) {
+    let notice = CreateMessage::new().content(format!(
+        "I can't post into <#{}> anymore ({}), notifications go to your default channel or \
+         direct messages until you pick a channel again with /change_notification_channel or \
+         /notification_settings.",
+        channel_id, reason
+    ));
+    if let Err(e) = UserId::new(discord_user_id as u64)
+        .direct_message(&ctx.discord_http, notice)
+        .await
+    {
+        warn!(
+            "Error telling user {} about the channel: {:?}",
+            discord_user_id, e
+        );
+    }
+}
+
+/// Always sends a DM to the linked Discord user.
+pub struct DirectMessageNotifier;
+
+#[async_trait]
+impl Notifier for DirectMessageNotifier {
+    async fn notify(
+        &self,
+        ctx: &CronAppContext,
+        character: &EvECharacterInfo,
+        event: &NotificationEvent,
+        delivery_id: DeliveryId,
+    ) -> Result<(), NotifyError> {
+        let target =
+            NotificationTarget::DirectMessage(UserId::new(character.discord_user_id as u64));
+        let channel_id = target.channel_id(&ctx.discord_http).await?;
+        send(ctx, channel_id, event, delivery_id).await?;
+        Ok(())
+    }
+}
+
+/// Posts the message of the event. Discord drops a second message with the same nonce, so a
+/// retried delivery doesn't show up twice.
+async fn send(
+    ctx: &CronAppContext,
+    channel_id: ChannelId,
+    event: &NotificationEvent,
+    delivery_id: DeliveryId,
+) -> serenity::Result<Message> {
+    let mut message = event.discord.clone();
+    if let Some(message) = message.as_object_mut() {
+        message.insert("nonce".to_string(), json!(delivery_id.nonce()));
+        message.insert("enforce_nonce".to_string(), json!(true));
+    }
+    ctx.discord_http
+        .send_message(channel_id, vec![], &message)
+        .await
+}
+
+/// Keeps one message per raffle: the first event of a raffle is posted, later ones edit that
+/// message as long as it is in the same channel. Reminders are posted anyway, an edit would go
+/// unnoticed.
+async fn send_live(
+    ctx: &CronAppContext,
+    channel_id: ChannelId,
+    event: &NotificationEvent,
+    delivery_id: DeliveryId,
+) -> Result<(), NotifyError> {
+    let Some(raffle) = event
+        .raffle
+        .as_ref()
+        .filter(|_| event.kind != NotificationEventKind::ResultReminder)
+    else {
+        send(ctx, channel_id, event, delivery_id).await?;
+        return Ok(());
+    };
+
+    let live = query_file!(
+        "./sql/hypernet_raffle/select_live_message.sql",
+        raffle.raffle_id,
+        raffle.character_id
+    )
+    .fetch_one(&ctx.postgres)
+    .await
+    .map_err(|e| NotifyError::Transient(e.into()))?;
+
+    if let (Some(live_channel_id), Some(message_id)) =
+        (live.discord_channel_id, live.discord_message_id)
+    {
+        if live_channel_id as u64 == channel_id.get() {
+            match edit_live(ctx, channel_id, MessageId::new(message_id as u64), event).await {
+                Ok(()) => return Ok(()),
+                // Somebody deleted the message, a new one is posted below.
+                Err(e) if is_not_found(&e) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    let message = send(ctx, channel_id, event, delivery_id).await?;
+    query_file!(
+        "./sql/hypernet_raffle/update_live_message.sql",
+        raffle.raffle_id,
+        raffle.character_id,
+        channel_id.get() as i64,
+        message.id.get() as i64
+    )
+    .execute(&ctx.postgres)
+    .await
+    .map_err(|e| NotifyError::Transient(e.into()))?;
+    Ok(())
+}
+
+async fn edit_live(
+    ctx: &CronAppContext,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    event: &NotificationEvent,
+) -> serenity::Result<()> {
+    let payload = match (&event.raffle, event.kind) {
+        // A result only marks the message, the valuation shown stays as it is.
+        (Some(raffle), NotificationEventKind::ResultSet) => {
+            let message = ctx.discord_http.get_message(channel_id, message_id).await?;
+            serde_json::to_value(
+                EditMessage::new()
+                    .embeds(result_embeds(&message.embeds, raffle.result))
+                    .components(vec![CreateActionRow::Buttons(result_buttons(
+                        &raffle.key(),
+                    ))]),
+            )?
+        }
+        // Only what an edit accepts, the rest of the posted message can't change.
+        _ => json!({
+            "content": event.discord.get("content"),
+            "embeds": event.discord.get("embeds").cloned().unwrap_or(json!([])),
+            "components": event.discord.get("components").cloned().unwrap_or(json!([])),
+        }),
+    };
+    ctx.discord_http
+        .edit_message(channel_id, message_id, &payload, vec![])
+        .await?;
+    Ok(())
+}
+
+fn is_not_found(error: &serenity::Error) -> bool {
+    matches!(
+        error,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response))
+            if response.status_code.as_u16() == 404
+    )
+}
+
+/// Marks the embeds of a raffle message with its result. An earlier mark is replaced, so a
+/// message can be marked again after a correction.
+pub fn result_embeds(embeds: &[Embed], result: HypernetRaffleResult) -> Vec<CreateEmbed> {
+    let (suffix, color) = match result {
+        HypernetRaffleResult::Winner => (Some(" - Won"), Color::from_rgb(241, 196, 15)),
+        HypernetRaffleResult::Loser => (Some(" - Loss"), Color::from_rgb(230, 126, 34)),
+        HypernetRaffleResult::None => (None, Color::from_rgb(0, 255, 0)),
+    };
+    embeds
+        .iter()
+        .map(|embed| {
+            let title = embed.title.clone().unwrap_or_default();
+            let title = title
+                .strip_suffix(" - Won")
+                .or(title.strip_suffix(" - Loss"))
+                .unwrap_or(&title);
+            CreateEmbed::from(embed.clone())
+                .title(format!("{}{}", title, suffix.unwrap_or_default()))
+                .color(color)
+        })
+        .collect()
+}
+
+/// The buttons of a raffle message once its result is known. The result buttons stay disabled
+/// until the owner asks to change the result.
+pub fn result_buttons(raffle_key: &str) -> Vec<CreateButton> {
+    vec![
+        CreateButton::new("raffle-won:".to_string() + raffle_key)
+            .label("Won Raffle")
+            .style(ButtonStyle::Success)
+            .disabled(true),
+        CreateButton::new("raffle-lost:".to_string() + raffle_key)
+            .label("Lost Raffle")
+            .style(ButtonStyle::Danger)
+            .disabled(true),
+        CreateButton::new("raffle-change:".to_string() + raffle_key)
+            .label("Change Result")
+            .style(ButtonStyle::Secondary),
+        CreateButton::new("open-market:".to_string() + raffle_key)
+            .label("Open Market")
+            .style(ButtonStyle::Primary),
+    ]
+}
+
+/// The buttons offered to correct a result, only the other outcome can be picked.
+pub fn correction_buttons(raffle_key: &str, current: HypernetRaffleResult) -> Vec<CreateButton> {
+    vec![
+        CreateButton::new("raffle-won:".to_string() + raffle_key)
+            .label("Won Raffle")
+            .style(ButtonStyle::Success)
+            .disabled(current == HypernetRaffleResult::Winner),
+        CreateButton::new("raffle-lost:".to_string() + raffle_key)
+            .label("Lost Raffle")
+            .style(ButtonStyle::Danger)
+            .disabled(current == HypernetRaffleResult::Loser),
+        CreateButton::new("open-market:".to_string() + raffle_key)
+            .label("Open Market")
+            .style(ButtonStyle::Primary),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::hypernet_raffle_model::HypernetRaffleResult;
+    use crate::notify::discord::result_embeds;
+    use serenity::all::Embed;
+
+    #[tokio::test]
+    async fn result_embeds_test() {
+        let mut embed = Embed::default();
+        embed.title = Some("Hypernet Raffle Finished".to_string());
+
+        let won = serde_json::to_value(&result_embeds(&[embed], HypernetRaffleResult::Winner)[0])
+            .unwrap();
+        assert_eq!(won["title"], "Hypernet Raffle Finished - Won");
+        assert_eq!(won["color"], 0xF1C40F);
+
+        // A correction replaces the earlier result instead of appending to it
+        let mut embed = Embed::default();
+        embed.title = Some("Hypernet Raffle Finished - Won".to_string());
+        let lost =
+            serde_json::to_value(&result_embeds(&[embed], HypernetRaffleResult::Loser)[0]).unwrap();
+        assert_eq!(lost["title"], "Hypernet Raffle Finished - Loss");
+        assert_eq!(lost["color"], 0xE67E22);
+    }
+}
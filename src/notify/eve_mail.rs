@@ -0,0 +1,47 @@
+use crate::context::CronAppContext;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::notify::{DeliveryId, NotificationEvent, Notifier, NotifyError};
+use async_trait::async_trait;
+use rfesi::prelude::RequestType;
+use serde_json::json;
+
+/// Sends the event as an in-game mail from the character to itself.
+pub struct EveMailNotifier;
+
+#[async_trait]
+impl Notifier for EveMailNotifier {
+    async fn notify(
+        &self,
+        ctx: &CronAppContext,
+        character: &EvECharacterInfo,
+        event: &NotificationEvent,
+        _delivery_id: DeliveryId,
+    ) -> Result<(), NotifyError> {
+        let mut esi = ctx.esi.clone();
+        esi.use_refresh_token(&character.refresh_token)
+            .await
+            .map_err(|e| NotifyError::Transient(e.into()))?;
+
+        // rfesi does not expose sending mail, so we query the endpoint ourselves.
+        let path = esi
+            .get_endpoint_for_op_id("post_characters_character_id_mail")
+            .map_err(|e| NotifyError::Transient(e.into()))?
+            .replace("{character_id}", &character.character_id.to_string());
+        let body = json!({
+            "recipients": [{
+                "recipient_id": character.character_id,
+                "recipient_type": "character",
+            }],
+            "subject": event.title(),
+            "body": event.text().replace('\n', "<br>"),
+            "approved_cost": 0,
+        })
+        .to_string();
+
+        let _mail_id: i64 = esi
+            .query("POST", RequestType::Authenticated, &path, None, Some(&body))
+            .await
+            .map_err(|e| NotifyError::Transient(e.into()))?;
+        Ok(())
+    }
+}
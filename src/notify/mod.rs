@@ -0,0 +1,354 @@
+pub mod digest;
+pub mod discord;
+mod eve_mail;
+mod webhook;
+
+use crate::context::CronAppContext;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::hypernet_raffle_model::{
+    EvEHypernetRaffle, HypernetRaffleResult, HypernetRaffleRole, HypernetRaffleStatus,
+};
+use crate::database::notification_preference::{
+    NotificationBackend, NotificationEventKind, NotificationPreference,
+};
+use crate::database::outbox_message::OutboxMessage;
+use crate::hypernet::profit::{estimated_profit, raffle_expected_value, ProfitView};
+use crate::notify::discord::{DirectMessageNotifier, DiscordChannelNotifier};
+use crate::notify::eve_mail::EveMailNotifier;
+use crate::notify::webhook::WebhookNotifier;
+pub use crate::notify::webhook::{
+    deliver_test, resolve_public_url, verify_signature, EVENT_HEADER, SIGNATURE_HEADER,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serenity::all::{ChannelId, Colour, CreateEmbed, CreateMessage, Http, HttpError, UserId};
+use sqlx::{query_file, query_file_as, Executor, PgConnection, Postgres};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use thousands::Separable;
+
+/// First retry delay of a failed outbox message, doubled with every further attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+/// Upper bound of the retry delay.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Something that happened to a character, delivered through the backends the user picked.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NotificationEvent {
+    pub kind: NotificationEventKind,
+    pub character_id: i32,
+    pub character_name: String,
+    /// The raffle as it was when the event happened.
+    pub raffle: Option<EvEHypernetRaffle>,
+    pub occurred_at: chrono::DateTime<Utc>,
+    /// The rendered Discord message, a serialized `CreateMessage`. The other backends derive
+    /// their text from it.
+    pub discord: Value,
+}
+
+impl NotificationEvent {
+    pub fn new(
+        kind: NotificationEventKind,
+        character: &EvECharacterInfo,
+        raffle: Option<&EvEHypernetRaffle>,
+        message: &CreateMessage,
+    ) -> anyhow::Result<Self> {
+        Ok(NotificationEvent {
+            kind,
+            character_id: character.character_id,
+            character_name: character.character_name.clone(),
+            raffle: raffle.cloned(),
+            occurred_at: Utc::now(),
+            discord: serde_json::to_value(message)?,
+        })
+    }
+
+    /// Title of the first embed.
+    pub fn title(&self) -> String {
+        self.discord["embeds"][0]["title"]
+            .as_str()
+            .map(|x| x.to_string())
+            .unwrap_or_else(|| self.kind.to_string())
+    }
+
+    /// The embeds as plain text lines, for backends that can't show embeds.
+    pub fn text(&self) -> String {
+        let mut lines = vec![];
+        if let Some(content) = self.discord["content"].as_str() {
+            lines.push(content.to_string());
+        }
+        for embed in self.discord["embeds"].as_array().into_iter().flatten() {
+            if let Some(description) = embed["description"].as_str() {
+                lines.push(description.to_string());
+            }
+            for field in embed["fields"].as_array().into_iter().flatten() {
+                lines.push(format!(
+                    "{}: {}",
+                    field["name"].as_str().unwrap_or_default(),
+                    field["value"].as_str().unwrap_or_default()
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug)]
+pub enum NotifyError {
+    /// Retrying won't help, e.g. the user can't be reached or has no webhook.
+    Permanent(anyhow::Error),
+    /// The target was disabled and the event should be retried right away.
+    Redirected(anyhow::Error),
+    Transient(anyhow::Error),
+}
+
+impl Display for NotifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotifyError::Permanent(e) | NotifyError::Redirected(e) | NotifyError::Transient(e) => {
+                write!(f, "{}", e)
+            }
+        }
+    }
+}
+
+impl From<serenity::Error> for NotifyError {
+    fn from(error: serenity::Error) -> Self {
+        if is_permanent(&error) {
+            NotifyError::Permanent(error.into())
+        } else {
+            NotifyError::Transient(error.into())
+        }
+    }
+}
+
+/// One delivery of an event, the same for every attempt. Deliveries are at least once, a send
+/// that wasn't confirmed is repeated. Every backend passes the id on so the receiver can drop the
+/// duplicate: webhooks get it as `delivery_id`, Discord messages carry it as their nonce. EVE mail
+/// has nothing of the kind and may arrive twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryId {
+    /// A single outbox message.
+    Outbox(i64),
+    /// A digest bundling several outbox messages.
+    Digest(i64),
+}
+
+impl DeliveryId {
+    /// The id as Discord message nonce, which is limited to 25 characters.
+    pub fn nonce(&self) -> String {
+        match self {
+            DeliveryId::Outbox(id) => format!("o{}", id),
+            DeliveryId::Digest(id) => format!("d{}", id),
+        }
+    }
+}
+
+/// A way of delivering notification events to a user.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(
+        &self,
+        ctx: &CronAppContext,
+        character: &EvECharacterInfo,
+        event: &NotificationEvent,
+        delivery_id: DeliveryId,
+    ) -> Result<(), NotifyError>;
+}
+
+/// The notifier delivering an outbox message.
+pub fn notifier(message: &OutboxMessage) -> Box<dyn Notifier> {
+    match message.backend {
+        NotificationBackend::DiscordChannel => Box::new(DiscordChannelNotifier {
+            channel_id: message.channel_id,
+        }),
+        NotificationBackend::DirectMessage => Box::new(DirectMessageNotifier),
+        NotificationBackend::Webhook => Box::new(WebhookNotifier {
+            webhook_id: message.webhook_id,
+        }),
+        NotificationBackend::EveMail => Box::new(EveMailNotifier),
+    }
+}
+
+/// Where the Discord notifications of a character are delivered to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationTarget {
+    Channel(ChannelId),
+    DirectMessage(UserId),
+}
+
+/// Resolves the target of a character. Without a configured (and still enabled) channel the
+/// linked Discord user gets a direct message.
+pub async fn notification_target<'e, E>(
+    executor: E,
+    character: &EvECharacterInfo,
+) -> anyhow::Result<NotificationTarget>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let channel_id: Option<i64> = sqlx::query_file_scalar!(
+        "./sql/notification_channel/select_channel_for_user.sql",
+        character.character_id
+    )
+    .fetch_optional(executor)
+    .await?
+    .flatten();
+
+    Ok(match channel_id {
+        Some(channel_id) => NotificationTarget::Channel(ChannelId::new(channel_id as u64)),
+        None => NotificationTarget::DirectMessage(UserId::new(character.discord_user_id as u64)),
+    })
+}
+
+impl NotificationTarget {
+    /// The channel to post into, opening the DM channel if necessary.
+    pub async fn channel_id(&self, http: &Http) -> serenity::Result<ChannelId> {
+        match self {
+            NotificationTarget::Channel(channel_id) => Ok(*channel_id),
+            NotificationTarget::DirectMessage(user_id) => {
+                Ok(user_id.create_dm_channel(http).await?.id)
+            }
+        }
+    }
+}
+
+/// Stores an event in the outbox once for every backend the user picked for its kind, the
+/// sender task delivers it. Events the character turned off or that miss its thresholds are
+/// dropped.
+pub async fn enqueue_event(
+    conn: &mut PgConnection,
+    event: &NotificationEvent,
+) -> anyhow::Result<()> {
+    let preference: NotificationPreference = query_file_as!(
+        NotificationPreference,
+        "./sql/notification_preference/select_preference.sql",
+        event.character_id,
+        event.kind as NotificationEventKind
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .unwrap_or_else(|| NotificationPreference::default_for(event.character_id, event.kind));
+
+    if !wanted(&preference, event.raffle.as_ref()) {
+        return Ok(());
+    }
+
+    let payload = serde_json::to_string(event)?;
+    query_file!(
+        "./sql/notification_outbox/insert_message.sql",
+        event.character_id,
+        event.raffle.as_ref().map(|x| x.raffle_id.as_str()),
+        event.kind as NotificationEventKind,
+        payload,
+        preference.channel_id
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Whether an event passes the toggle and thresholds of a preference. Thresholds only apply to
+/// raffle events and let raffles through whose value isn't known.
+fn wanted(preference: &NotificationPreference, raffle: Option<&EvEHypernetRaffle>) -> bool {
+    if !preference.enabled {
+        return false;
+    }
+    let Some(raffle) = raffle else {
+        return true;
+    };
+    let below = |value: Option<f64>, min: Option<f64>| matches!((value, min), (Some(value), Some(min)) if value < min);
+    !below(raffle.sell_price, preference.min_value)
+        && !below(raffle_expected_value(raffle), preference.min_expected_value)
+}
+
+/// Queues a `ResultSet` event for a raffle whose result was just stored, within the same
+/// transaction.
+pub async fn enqueue_result(
+    conn: &mut PgConnection,
+    raffle_id: &str,
+    character_id: i32,
+) -> anyhow::Result<()> {
+    let raffle: EvEHypernetRaffle = query_file_as!(
+        EvEHypernetRaffle,
+        "./sql/hypernet_raffle/select_raffle_by_id.sql",
+        raffle_id,
+        character_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    let character: EvECharacterInfo = query_file_as!(
+        EvECharacterInfo,
+        "./sql/eve_character/select_character_by_id.sql",
+        raffle.character_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let (title, color) = match raffle.result {
+        HypernetRaffleResult::Winner => ("Hypernet Raffle Won", Colour::from_rgb(241, 196, 15)),
+        HypernetRaffleResult::Loser => ("Hypernet Raffle Lost", Colour::from_rgb(230, 126, 34)),
+        HypernetRaffleResult::None => return Ok(()),
+    };
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(format!(
+            "{} {} raffle {}.",
+            character.character_name,
+            raffle.result.to_string().to_lowercase(),
+            raffle.raffle_id
+        ))
+        .thumbnail(format!(
+            "https://images.evetech.net/types/{}/icon",
+            raffle.type_id
+        ))
+        .color(color)
+        .field(
+            "Profit (Market)",
+            estimated_profit(&raffle, ProfitView::MarkToMarket)
+                .map(|x| x.round().separate_with_dots())
+                .unwrap_or("Unknown".to_string()),
+            true,
+        );
+
+    let event = NotificationEvent::new(
+        NotificationEventKind::ResultSet,
+        &character,
+        Some(&raffle),
+        &CreateMessage::new().embed(embed),
+    )?;
+    enqueue_event(&mut *conn, &event).await
+}
+
+/// Errors that won't go away by retrying: the channel or user is gone or we lack access.
+pub fn is_permanent(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response)) => {
+            matches!(response.status_code.as_u16(), 403 | 404)
+        }
+        _ => false,
+    }
+}
+
+/// Backoff before the next attempt, after `attempts` failed ones.
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 16) as u32 - 1;
+    RETRY_BASE_DELAY
+        .saturating_mul(2u32.pow(exponent))
+        .min(RETRY_MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn retry_delay_test() {
+        assert_eq!(retry_delay(1), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(60));
+        assert_eq!(retry_delay(5), Duration::from_secs(480));
+        assert_eq!(retry_delay(12), RETRY_MAX_DELAY);
+        assert_eq!(retry_delay(100), RETRY_MAX_DELAY);
+    }
+}
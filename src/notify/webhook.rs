@@ -0,0 +1,373 @@
+use crate::context::CronAppContext;
+use crate::database::eve_character_info::EvECharacterInfo;
+use crate::database::hypernet_raffle_model::{EvEHypernetRaffle, HypernetRaffleRole};
+use crate::database::webhook_endpoint::WebhookEndpoint;
+use crate::hypernet::profit::ProfitType::{Loser, Winner};
+use crate::hypernet::profit::{
+    calculate_profit, estimated_profit, participant_profit, raffle_expected_value, ProfitView,
+    RaffleSetup,
+};
+use crate::hypernet::reconcile::realized_profit;
+use crate::notify::{DeliveryId, NotificationEvent, Notifier, NotifyError};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use log::warn;
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::{query_file, query_file_as, PgPool};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+/// Header carrying the hex encoded HMAC-SHA256 of the body, keyed with the endpoint secret.
+pub const SIGNATURE_HEADER: &str = "X-Hypernet-Signature";
+pub const EVENT_HEADER: &str = "X-Hypernet-Event";
+
+/// What a webhook receives.
+#[derive(Serialize, Debug)]
+struct WebhookPayload<'a> {
+    /// The outbox message, the same for every retry so receivers can drop duplicates (see
+    /// `DeliveryId`). Missing for test deliveries.
+    delivery_id: Option<i64>,
+    event: String,
+    occurred_at: chrono::DateTime<Utc>,
+    character_id: Option<i32>,
+    character_name: Option<&'a str>,
+    raffle: Option<WebhookRaffle<'a>>,
+}
+
+/// The raffle with the profit figures we show in Discord.
+#[derive(Serialize, Debug)]
+struct WebhookRaffle<'a> {
+    #[serde(flatten)]
+    raffle: &'a EvEHypernetRaffle,
+    expected_value: Option<f64>,
+    profit_if_won: Option<f64>,
+    profit_if_lost: Option<f64>,
+    /// Profit of the actual outcome at the stored market prices.
+    estimated_profit: Option<f64>,
+    estimated_profit_cost_basis: Option<f64>,
+    /// Profit according to the wallet journal, once entries were matched.
+    realized_profit: Option<f64>,
+}
+
+impl<'a> WebhookRaffle<'a> {
+    fn new(raffle: &'a EvEHypernetRaffle, realized_profit: Option<f64>) -> Self {
+        let setup = RaffleSetup::from(raffle);
+        let (profit_if_won, profit_if_lost) = match raffle.role {
+            HypernetRaffleRole::Owner => (
+                calculate_profit(&setup, Winner),
+                calculate_profit(&setup, Loser),
+            ),
+            HypernetRaffleRole::Participant => (
+                participant_profit(&setup, Winner),
+                participant_profit(&setup, Loser),
+            ),
+        };
+        WebhookRaffle {
+            raffle,
+            expected_value: raffle_expected_value(raffle),
+            profit_if_won,
+            profit_if_lost,
+            estimated_profit: estimated_profit(raffle, ProfitView::MarkToMarket),
+            estimated_profit_cost_basis: estimated_profit(raffle, ProfitView::CostBasis),
+            realized_profit,
+        }
+    }
+}
+
+/// POSTs the event as JSON to a user or guild webhook. Every attempt is logged.
+pub struct WebhookNotifier {
+    pub webhook_id: Option<i64>,
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(
+        &self,
+        ctx: &CronAppContext,
+        _character: &EvECharacterInfo,
+        event: &NotificationEvent,
+        delivery_id: DeliveryId,
+    ) -> Result<(), NotifyError> {
+        // Digests only go to Discord
+        let DeliveryId::Outbox(outbox_id) = delivery_id else {
+            return Err(NotifyError::Permanent(anyhow!(
+                "Webhooks only deliver outbox messages"
+            )));
+        };
+
+        let endpoint: Option<WebhookEndpoint> = match self.webhook_id {
+            Some(webhook_id) => query_file_as!(
+                WebhookEndpoint,
+                "./sql/webhook_endpoint/select_endpoint_by_id.sql",
+                webhook_id
+            )
+            .fetch_optional(&ctx.postgres)
+            .await
+            .map_err(|e| NotifyError::Transient(e.into()))?,
+            None => None,
+        };
+        let Some(endpoint) = endpoint else {
+            return Err(NotifyError::Permanent(anyhow!("No webhook registered")));
+        };
+
+        let realized = match &event.raffle {
+            Some(raffle) => {
+                let realized = query_file!(
+                    "./sql/raffle_wallet_entries/select_realized_for_raffle.sql",
+                    raffle.raffle_id,
+                    raffle.character_id
+                )
+                .fetch_one(&ctx.postgres)
+                .await
+                .map_err(|e| NotifyError::Transient(e.into()))?;
+                realized_profit(
+                    raffle,
+                    realized.isk_in,
+                    realized.isk_out,
+                    realized.settlements > 0,
+                )
+            }
+            None => None,
+        };
+
+        let payload = WebhookPayload {
+            delivery_id: Some(outbox_id),
+            event: event.kind.to_string(),
+            occurred_at: event.occurred_at,
+            character_id: Some(event.character_id),
+            character_name: Some(&event.character_name),
+            raffle: event
+                .raffle
+                .as_ref()
+                .map(|raffle| WebhookRaffle::new(raffle, realized)),
+        };
+        deliver(&ctx.postgres, &endpoint, Some(outbox_id), &payload).await
+    }
+}
+
+/// Sends a payload without a raffle, to check that the receiver works.
+pub async fn deliver_test(
+    postgres: &PgPool,
+    endpoint: &WebhookEndpoint,
+) -> Result<(), NotifyError> {
+    let payload = WebhookPayload {
+        delivery_id: None,
+        event: "Test".to_string(),
+        occurred_at: Utc::now(),
+        character_id: None,
+        character_name: None,
+        raffle: None,
+    };
+    deliver(postgres, endpoint, None, &payload).await
+}
+
+async fn deliver(
+    postgres: &PgPool,
+    endpoint: &WebhookEndpoint,
+    outbox_id: Option<i64>,
+    payload: &WebhookPayload<'_>,
+) -> Result<(), NotifyError> {
+    let body = serde_json::to_string(payload).map_err(|e| NotifyError::Permanent(e.into()))?;
+
+    let started = Instant::now();
+    let response = match public_client(&endpoint.url).await {
+        Ok(client) => Ok(client
+            .post(&endpoint.url)
+            .timeout(Duration::from_secs(10))
+            .header("Content-Type", "application/json")
+            .header(EVENT_HEADER, &payload.event)
+            .header(
+                SIGNATURE_HEADER,
+                format!("sha256={}", sign(&endpoint.secret, &body)),
+            )
+            .body(body)
+            .send()
+            .await),
+        Err(e) => Err(e),
+    };
+    let duration_ms = started.elapsed().as_millis() as i32;
+
+    let status_code = match &response {
+        Ok(Ok(response)) => Some(response.status().as_u16() as i32),
+        Ok(Err(e)) => e.status().map(|x| x.as_u16() as i32),
+        Err(_) => None,
+    };
+    let result = match response {
+        Err(e) => Err(e),
+        Ok(Ok(response)) if response.status().is_success() => Ok(()),
+        Ok(Ok(response)) => {
+            let status = response.status();
+            let error = anyhow!("Webhook answered {}", status);
+            if status.is_client_error() && status.as_u16() != 429 {
+                Err(NotifyError::Permanent(error))
+            } else {
+                Err(NotifyError::Transient(error))
+            }
+        }
+        Ok(Err(e)) => Err(NotifyError::Transient(e.into())),
+    };
+
+    let error = result.as_ref().err().map(|e| e.to_string());
+    if let Err(e) = query_file!(
+        "./sql/webhook_delivery/insert_delivery.sql",
+        endpoint.id,
+        outbox_id,
+        payload.event,
+        status_code,
+        error,
+        duration_ms
+    )
+    .execute(postgres)
+    .await
+    {
+        warn!("Error logging delivery to webhook {}: {:?}", endpoint.id, e);
+    }
+
+    result
+}
+
+/// Resolves the host of a webhook URL and makes sure every address it points to is public.
+/// Webhooks are set by users and must not reach into the network the bot runs in.
+pub async fn resolve_public_url(url: &str) -> Result<(reqwest::Url, Vec<SocketAddr>), NotifyError> {
+    let url = reqwest::Url::parse(url).map_err(|e| NotifyError::Permanent(e.into()))?;
+    if url.scheme() != "https" {
+        return Err(NotifyError::Permanent(anyhow!(
+            "Webhooks have to use https"
+        )));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| NotifyError::Permanent(anyhow!("The URL has no host")))?;
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    let addrs: Vec<SocketAddr> = match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| NotifyError::Transient(e.into()))?
+            .collect(),
+    };
+    if addrs.is_empty() {
+        return Err(NotifyError::Transient(anyhow!("{} did not resolve", host)));
+    }
+    if let Some(addr) = addrs.iter().find(|x| !is_public(x.ip())) {
+        return Err(NotifyError::Permanent(anyhow!(
+            "{} points to the non-public address {}",
+            host,
+            addr.ip()
+        )));
+    }
+    Ok((url, addrs))
+}
+
+/// A client that only connects to the checked addresses of the URL, so the host can't resolve
+/// to something else in between, and does not follow redirects to other hosts.
+async fn public_client(url: &str) -> Result<reqwest::Client, NotifyError> {
+    let (url, addrs) = resolve_public_url(url).await?;
+    let mut builder = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+    if let Some(host) = url.host_str() {
+        builder = builder.resolve_to_addrs(host, &addrs);
+    }
+    builder
+        .build()
+        .map_err(|e| NotifyError::Transient(e.into()))
+}
+
+/// Whether an address is reachable on the internet, not loopback, private, link-local or
+/// otherwise reserved.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                // Shared address space (RFC 6598)
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(ip.into()),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Checks a `sha256=<hex>` signature header in constant time.
+pub fn verify_signature(secret: &str, body: &[u8], header: &str) -> bool {
+    let Some(signature) = header
+        .strip_prefix("sha256=")
+        .and_then(|x| hex::decode(x).ok())
+    else {
+        return false;
+    };
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sign_test() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let body = r#"{"event":"Test"}"#;
+        let header = format!("sha256={}", sign("secret", body));
+        assert!(verify_signature("secret", body.as_bytes(), &header));
+        assert!(!verify_signature("other", body.as_bytes(), &header));
+        assert!(!verify_signature("secret", body.as_bytes(), "sha256=zz"));
+    }
+
+    #[tokio::test]
+    async fn resolve_public_url_test() {
+        for url in [
+            "https://169.254.169.254/latest/meta-data",
+            "https://127.0.0.1:8080/",
+            "https://localhost:8080/",
+            "https://10.0.0.1/",
+            "https://192.168.1.1/",
+            "https://[::1]/",
+            "https://[fe80::1]/",
+            "https://[::ffff:127.0.0.1]/",
+        ] {
+            assert!(
+                matches!(
+                    resolve_public_url(url).await,
+                    Err(NotifyError::Permanent(_))
+                ),
+                "{}",
+                url
+            );
+        }
+        assert!(resolve_public_url("http://1.1.1.1/").await.is_err());
+        assert!(resolve_public_url("https://1.1.1.1/hook").await.is_ok());
+    }
+}
@@ -1,2 +1,3 @@
 mod api_error;
 pub mod callback;
+pub mod webhook_test;
@@ -0,0 +1,56 @@
+use crate::context::AppContext;
+use crate::database::webhook_endpoint::WebhookEndpoint;
+use crate::notify::{verify_signature, EVENT_HEADER, SIGNATURE_HEADER};
+use crate::rest::api_error::ApiError;
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use actix_web::{post, web, HttpRequest, HttpResponse};
+use log::info;
+use serde_json::json;
+use sqlx::query_file_as;
+
+/// Receives webhook deliveries like a user's server would, for checking them locally. Point a
+/// webhook at `/webhooks/test/{webhook_id}` and the signature is verified with its secret.
+#[post("/webhooks/test/{webhook_id}")]
+async fn webhook_test(
+    ctx: web::Data<AppContext>,
+    webhook_id: web::Path<i64>,
+    request: HttpRequest,
+    body: Bytes,
+) -> Result<HttpResponse, ApiError> {
+    let endpoint: WebhookEndpoint = query_file_as!(
+        WebhookEndpoint,
+        "./sql/webhook_endpoint/select_endpoint_by_id.sql",
+        webhook_id.into_inner()
+    )
+    .fetch_optional(&ctx.postgres)
+    .await?
+    .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Unknown webhook"))?;
+
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|x| x.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let signature = header(SIGNATURE_HEADER);
+    if !verify_signature(&endpoint.secret, &body, &signature) {
+        return Err(ApiError::new_with_title(
+            StatusCode::UNAUTHORIZED,
+            "Invalid signature",
+            "The signature does not match the body",
+        ));
+    }
+
+    let event = header(EVENT_HEADER);
+    let delivery_id = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|x| x.get("delivery_id").and_then(|x| x.as_i64()));
+    info!(
+        "Test webhook {} received {} (delivery {:?})",
+        endpoint.id, event, delivery_id
+    );
+    Ok(HttpResponse::Ok().json(json!({ "valid": true, "event": event })))
+}